@@ -0,0 +1,78 @@
+use crate::ray::*;
+use crate::vec3::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub minimum: Point3<f64>,
+    pub maximum: Point3<f64>
+}
+
+impl Aabb {
+    pub fn new(minimum: Point3<f64>, maximum: Point3<f64>) -> Self { Self { minimum, maximum } }
+
+    pub fn zero() -> Self {
+        Self::new(Point3::zero(), Point3::zero())
+    }
+
+    // Smallest box containing both corner points, in any order.
+    pub fn from_points(a: Point3<f64>, b: Point3<f64>) -> Self {
+        Self::new(
+            Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        )
+    }
+
+    pub fn hit(&self, r: &Ray<f64>, t_min: f64, t_max: f64) -> bool {
+        let min = self.minimum.to_vec();
+        let max = self.maximum.to_vec();
+        let origin = r.origin.to_vec();
+        let direction = r.direction.to_vec();
+
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
+            let inv_d = 1.0 / direction[a];
+            let mut t0 = (min[a] - origin[a]) * inv_d;
+            let mut t1 = (max[a] - origin[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Grow any side thinner than `delta`, so planar shapes still get a box
+    // rays can hit.
+    pub fn pad(&self, delta: f64) -> Self {
+        let grow = |min: f64, max: f64| if max - min < delta { (min - delta / 2.0, max + delta / 2.0) } else { (min, max) };
+        let (x0, x1) = grow(self.minimum.x, self.maximum.x);
+        let (y0, y1) = grow(self.minimum.y, self.maximum.y);
+        let (z0, z1) = grow(self.minimum.z, self.maximum.z);
+        Self::new(Point3::new(x0, y0, z0), Point3::new(x1, y1, z1))
+    }
+}
+
+// Box around a disk; it extends radius * sin(angle between normal and each
+// world axis) from the center.
+pub fn disk_box(center: Point3<f64>, normal: Vec3<f64>, radius: f64) -> Aabb {
+    let n = normal.unit_vector();
+    let e = radius * Vec3::new((1.0 - n.x * n.x).max(0.0).sqrt(),
+                               (1.0 - n.y * n.y).max(0.0).sqrt(),
+                               (1.0 - n.z * n.z).max(0.0).sqrt());
+    Aabb::new(center - e, center + e)
+}
+
+pub fn surrounding_box(box0: &Aabb, box1: &Aabb) -> Aabb {
+    let small = Point3::new(box0.minimum.x.min(box1.minimum.x),
+                            box0.minimum.y.min(box1.minimum.y),
+                            box0.minimum.z.min(box1.minimum.z));
+    let big = Point3::new(box0.maximum.x.max(box1.maximum.x),
+                          box0.maximum.y.max(box1.maximum.y),
+                          box0.maximum.z.max(box1.maximum.z));
+    Aabb::new(small, big)
+}
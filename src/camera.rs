@@ -1,7 +1,36 @@
-use crate::utilities::degrees_to_radians;
+use crate::constants::{INFINITY, PI};
+use crate::utilities::{degrees_to_radians, random_double};
 use crate::vec3::*;
 use crate::ray::*;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    // Pinhole or thin lens, rays spreading out from `lookfrom`.
+    Perspective,
+    // Parallel rays along the view direction, starting on the plane through
+    // `lookfrom`.
+    Orthographic,
+    // Latitude-longitude panorama of the whole sphere, with the view
+    // direction at the center, laid out like `EnvironmentMap`. Meant for 2:1
+    // images.
+    Equirectangular,
+    // Six square faces in a 3:2 grid: +x, -x, +y along the top row and -y,
+    // +z, -z along the bottom, in camera space with the view along -z, each
+    // oriented as in OpenGL cube maps.
+    CubeMap,
+    // Circular fisheye covering `fov` degrees across the image height.
+    Fisheye { fov: f64, aspect_ratio: f64, mapping: FisheyeMapping },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    // Distance from the center proportional to the angle off axis.
+    Equidistant,
+    // Equal areas of the image cover equal solid angles.
+    Equisolid,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct Camera {
     origin: Point3<f64>,
     lower_left_corner: Point3<f64>,
@@ -10,7 +39,16 @@ pub struct Camera {
     u: Vec3<f64>,
     v: Vec3<f64>,
     w: Vec3<f64>,
-    lens_radius: f64
+    lens_radius: f64,
+    projection: Projection,
+    // Signed distance of a panoramic eye from the center of projection along
+    // the right of each view direction, and the distance at which the eyes
+    // converge, for omni-directional stereo.
+    eye_offset: f64,
+    convergence: f64,
+    // Shutter open/close times
+    time0: f64,
+    time1: f64
 }
 
 impl Camera {
@@ -31,15 +69,280 @@ impl Camera {
 
         let len_radius = aperture / 2.0;
 
-        Camera { origin: origin, lower_left_corner: lower_left_corner, horizontal: horizontal , vertical: vertical, u: u, v: v, w: w, lens_radius: len_radius }
+        Camera { origin, lower_left_corner, horizontal, vertical, u, v, w, lens_radius: len_radius, projection: Projection::Perspective, eye_offset: 0.0, convergence: INFINITY, time0: 0.0, time1: 0.0 }
+    }
+
+    // Orthographic view `view_width` across, looking from `lookfrom` towards
+    // `lookat`. Everything in front of the plane through `lookfrom` is in
+    // focus.
+    pub fn orthographic(lookfrom: Point3<f64>, lookat: Point3<f64>, vup: Vec3<f64>, view_width: f64, aspect_ratio: f64) -> Camera {
+        let w = (lookfrom - lookat).unit_vector();
+        let u = vup.cross(w).unit_vector();
+        let v = w.cross(u);
+
+        let origin = lookfrom;
+        let horizontal = view_width * u;
+        let vertical = view_width / aspect_ratio * v;
+        let lower_left_corner = origin - horizontal/2.0 - vertical/2.0;
+
+        Camera { origin, lower_left_corner, horizontal, vertical, u, v, w, lens_radius: 0.0, projection: Projection::Orthographic, eye_offset: 0.0, convergence: INFINITY, time0: 0.0, time1: 0.0 }
+    }
+
+    // Panoramas seen from `lookfrom` with the view direction towards
+    // `lookat`. They ignore the image's aspect ratio, except for the fisheye,
+    // whose image circle spans the height of the image.
+    pub fn equirectangular(lookfrom: Point3<f64>, lookat: Point3<f64>, vup: Vec3<f64>) -> Camera {
+        Self::panoramic(lookfrom, lookat, vup, Projection::Equirectangular)
+    }
+
+    pub fn cube_map(lookfrom: Point3<f64>, lookat: Point3<f64>, vup: Vec3<f64>) -> Camera {
+        Self::panoramic(lookfrom, lookat, vup, Projection::CubeMap)
     }
 
-    pub fn get_ray(&self, s: f64, t:f64) -> Ray<f64> {
-        let rd = self.lens_radius * random_in_unit_disk();
-        let offset = self.u * rd.x + self.v * rd.y;
+    pub fn fisheye(lookfrom: Point3<f64>, lookat: Point3<f64>, vup: Vec3<f64>, fov: f64, aspect_ratio: f64, mapping: FisheyeMapping) -> Camera {
+        Self::panoramic(lookfrom, lookat, vup, Projection::Fisheye { fov, aspect_ratio, mapping })
+    }
+
+    fn panoramic(lookfrom: Point3<f64>, lookat: Point3<f64>, vup: Vec3<f64>, projection: Projection) -> Camera {
+        let w = (lookfrom - lookat).unit_vector();
+        let u = vup.cross(w).unit_vector();
+        let v = w.cross(u);
+        Camera { origin: lookfrom, lower_left_corner: lookfrom, horizontal: u, vertical: v, u, v, w, lens_radius: 0.0, projection, eye_offset: 0.0, convergence: INFINITY, time0: 0.0, time1: 0.0 }
+    }
+
+    pub fn with_shutter(mut self, time0: f64, time1: f64) -> Camera {
+        self.time0 = time0;
+        self.time1 = time1;
+        self
+    }
 
-        let origin = self.origin + offset;
-        let direction = self.lower_left_corner + s * self.horizontal + t * self.vertical - origin - offset;
-        Ray::new(origin, direction)
+    // The same view from an eye `offset` to the right (negative for left),
+    // with zero parallax at `convergence`. Perspective eyes are shifted along
+    // the camera's u axis with off-axis frusta, so they share the window at
+    // the convergence distance. Panoramic eyes orbit the center of projection
+    // instead, offset to the right of each view direction (omni-directional
+    // stereo), by less towards the poles so the eyes merge there.
+    pub fn eye(&self, offset: f64, convergence: f64) -> Camera {
+        let mut eye = *self;
+        match self.projection {
+            Projection::Perspective | Projection::Orthographic => {
+                let focus_dist = (self.origin - self.lower_left_corner - self.horizontal / 2.0 - self.vertical / 2.0).dot(self.w);
+                eye.origin = self.origin + offset * self.u;
+                eye.lower_left_corner = self.lower_left_corner + offset * (1.0 - focus_dist / convergence) * self.u;
+            }
+            _ => {
+                eye.eye_offset = offset;
+                eye.convergence = convergence;
+            }
+        }
+        eye
     }
-}
\ No newline at end of file
+
+    // None where the image has no view, outside a fisheye's image circle.
+    pub fn get_ray(&self, s: f64, t:f64) -> Option<Ray<f64>> {
+        let time = random_double(self.time0, self.time1);
+        let target = self.lower_left_corner + s * self.horizontal + t * self.vertical;
+        let direction = match self.projection {
+            Projection::Perspective => {
+                let rd = self.lens_radius * random_in_unit_disk();
+                let offset = self.u * rd.x + self.v * rd.y;
+
+                let origin = self.origin + offset;
+                return Some(Ray::with_time(origin, target - origin, time));
+            }
+            Projection::Orthographic => return Some(Ray::with_time(target, -self.w, time)),
+            Projection::Equirectangular => {
+                let (theta, phi) = ((1.0 - t) * PI, (s - 0.5) * 2.0 * PI);
+                Vec3::new(theta.sin() * phi.sin(), theta.cos(), -theta.sin() * phi.cos())
+            }
+            Projection::CubeMap => {
+                let column = ((s * 3.0) as usize).min(2);
+                let row = ((t * 2.0) as usize).min(1);
+                let a = 2.0 * (s * 3.0 - column as f64) - 1.0;
+                let b = 2.0 * (t * 2.0 - row as f64) - 1.0;
+                match (row, column) {
+                    (1, 0) => Vec3::new(1.0, b, -a),
+                    (1, 1) => Vec3::new(-1.0, b, a),
+                    (1, _) => Vec3::new(a, 1.0, -b),
+                    (_, 0) => Vec3::new(a, -1.0, b),
+                    (_, 1) => Vec3::new(a, b, 1.0),
+                    _ => Vec3::new(-a, b, -1.0),
+                }
+            }
+            Projection::Fisheye { fov, aspect_ratio, mapping } => {
+                let (x, y) = ((2.0 * s - 1.0) * aspect_ratio, 2.0 * t - 1.0);
+                let r = (x * x + y * y).sqrt();
+                if r > 1.0 {
+                    return None;
+                }
+                let theta_max = degrees_to_radians(fov) / 2.0;
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => r * theta_max,
+                    FisheyeMapping::Equisolid => 2.0 * (r * (theta_max / 2.0).sin()).clamp(-1.0, 1.0).asin(),
+                };
+                let phi = y.atan2(x);
+                Vec3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), -theta.cos())
+            }
+        };
+        // From camera space to the world.
+        let direction = direction.x * self.u + direction.y * self.v + direction.z * self.w;
+        if self.eye_offset == 0.0 {
+            return Some(Ray::with_time(self.origin, direction, time));
+        }
+        let offset = self.eye_offset * direction.unit_vector().cross(self.v);
+        let direction = if self.convergence < INFINITY { self.convergence * direction.unit_vector() - offset } else { direction };
+        Some(Ray::with_time(self.origin + offset, direction, time))
+    }
+}
+
+// Full frame 35mm sensor, in millimetres.
+pub const FULL_FRAME_WIDTH: f64 = 36.0;
+pub const FULL_FRAME_HEIGHT: f64 = 24.0;
+
+// Camera settings as on a real body and lens. Scene units are metres, shutter
+// time is in the same seconds as object motion, and radiance is expected in
+// cd/m^2 for the exposure to match a photograph.
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicalCamera {
+    // Millimetres.
+    pub focal_length: f64,
+    pub sensor_width: f64,
+    pub sensor_height: f64,
+    pub f_number: f64,
+    // Seconds.
+    pub shutter: f64,
+    pub iso: f64
+}
+
+impl PhysicalCamera {
+    pub fn new(focal_length: f64, f_number: f64, shutter: f64, iso: f64) -> Self {
+        Self { focal_length, sensor_width: FULL_FRAME_WIDTH, sensor_height: FULL_FRAME_HEIGHT, f_number, shutter, iso }
+    }
+
+    pub fn with_sensor(mut self, width: f64, height: f64) -> Self {
+        self.sensor_width = width;
+        self.sensor_height = height;
+        self
+    }
+
+    // Vertical field of view in degrees, with the lens focused at infinity.
+    pub fn vfov(&self) -> f64 {
+        (2.0 * (self.sensor_height / (2.0 * self.focal_length)).atan()).to_degrees()
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.sensor_width / self.sensor_height
+    }
+
+    // Diameter of the entrance pupil, in metres.
+    pub fn aperture(&self) -> f64 {
+        self.focal_length / self.f_number / 1000.0
+    }
+
+    // Factor taking scene luminance to image values, with 1 at the sensor's
+    // saturation: t S / (N^2 * 78 / q) for the ISO 12232 saturation based
+    // speed, with lens and vignetting factor q = 0.65.
+    pub fn exposure(&self) -> f64 {
+        self.shutter * self.iso / (self.f_number * self.f_number * 120.0)
+    }
+
+    // Perspective camera with these settings for an image of `aspect_ratio`,
+    // focused at `focus_dist`, and the shutter open from time 0. The image
+    // covers the largest centered crop of the sensor with its aspect ratio.
+    pub fn camera(&self, lookfrom: Point3<f64>, lookat: Point3<f64>, vup: Vec3<f64>, aspect_ratio: f64, focus_dist: f64) -> Camera {
+        let height = self.sensor_height.min(self.sensor_width / aspect_ratio);
+        let vfov = (2.0 * (height / (2.0 * self.focal_length)).atan()).to_degrees();
+        Camera::new(lookfrom, lookat, vup, vfov, aspect_ratio, self.aperture(), focus_dist).with_shutter(0.0, self.shutter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn thin_lens_center_ray_stays_on_axis() {
+        // Wherever on the lens it starts, the center ray passes through the
+        // point in focus straight ahead.
+        let cam = Camera::new(Point3::zero(), Point3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0), 40.0, 1.5, 2.0, 4.0);
+        for _ in 0..10 {
+            let r = cam.get_ray(0.5, 0.5).unwrap();
+            assert!((r.origin + r.direction - Point3::new(0.0, 0.0, -4.0)).length() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let cam = Camera::orthographic(Point3::new(0.0, 10.0, 0.0), Point3::zero(), Vec3::new(0.0, 0.0, -1.0), 8.0, 2.0);
+        let corner = cam.get_ray(0.0, 0.0).unwrap();
+        let center = cam.get_ray(0.5, 0.5).unwrap();
+        assert!((corner.direction - Vec3::new(0.0, -1.0, 0.0)).length() < 1e-12);
+        assert!((center.direction - corner.direction).length() < 1e-12);
+        assert!((center.origin - Point3::new(0.0, 10.0, 0.0)).length() < 1e-12);
+
+        // Top down with -z up: the view is 8 wide in x and 4 tall in z.
+        assert!((corner.origin - Point3::new(-4.0, 10.0, 2.0)).length() < 1e-12);
+    }
+
+    #[test]
+    fn panoramic_directions() {
+        let (lookfrom, lookat, vup) = (Point3::zero(), Point3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0));
+        let direction = |cam: &Camera, s: f64, t: f64| cam.get_ray(s, t).unwrap().direction.unit_vector();
+        let close = |a: Vec3<f64>, b: Vec3<f64>| (a - b).length() < 1e-9;
+
+        let equirect = Camera::equirectangular(lookfrom, lookat, vup);
+        assert!(close(direction(&equirect, 0.5, 0.5), Vec3::new(0.0, 0.0, -1.0)));
+        assert!(close(direction(&equirect, 0.75, 0.5), Vec3::new(1.0, 0.0, 0.0)));
+        assert!(close(direction(&equirect, 0.3, 1.0), Vec3::new(0.0, 1.0, 0.0)));
+
+        // Face centers of the 3x2 grid.
+        let cube = Camera::cube_map(lookfrom, lookat, vup);
+        let faces = [(0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (0.0, 0.0), (1.0, 0.0), (2.0, 0.0)];
+        let expected = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0)];
+        for ((column, row), e) in faces.iter().zip(expected) {
+            assert!(close(direction(&cube, (column + 0.5) / 3.0, (row + 0.5) / 2.0), e));
+        }
+
+        // A 180 degree fisheye sees sideways at the edge of its circle, where
+        // both mappings agree; halfway out they differ.
+        for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let fisheye = Camera::fisheye(lookfrom, lookat, vup, 180.0, 1.0, mapping);
+            assert!(close(direction(&fisheye, 1.0, 0.5), Vec3::new(1.0, 0.0, 0.0)));
+            assert!(fisheye.get_ray(0.99, 0.99).is_none());
+        }
+        let halfway = Camera::fisheye(lookfrom, lookat, vup, 180.0, 1.0, FisheyeMapping::Equidistant);
+        assert!((direction(&halfway, 0.5, 0.75).y - (PI / 4.0).sin()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn physical_settings() {
+        // A 50mm lens on full frame at f/2.
+        let settings = PhysicalCamera::new(50.0, 2.0, 1.0 / 125.0, 100.0);
+        assert!((settings.vfov() - 26.99).abs() < 0.01);
+        assert!((settings.aspect_ratio() - 1.5).abs() < 1e-12);
+        assert!((settings.aperture() - 0.025).abs() < 1e-12);
+
+        // One stop more on each of the three dials doubles the exposure three
+        // times over.
+        let brighter = PhysicalCamera { f_number: 2.0 / 2f64.sqrt(), shutter: 2.0 / 125.0, iso: 200.0, ..settings };
+        assert!((brighter.exposure() / settings.exposure() - 8.0).abs() < 1e-9);
+
+        // Sunny 16: at f/16 and 1/ISO seconds, a sunlit grey card of about
+        // 4000 cd/m^2 lands near middle grey.
+        let sunny = PhysicalCamera::new(50.0, 16.0, 1.0 / 100.0, 100.0);
+        let grey = 4000.0 * sunny.exposure();
+        assert!(grey > 0.1 && grey < 0.2, "got {}", grey);
+
+        // Images wider than the sensor crop its height, narrower ones its
+        // width, rather than stretching it.
+        let pinhole = PhysicalCamera { f_number: 1e9, ..settings };
+        let top_angle = |aspect: f64| {
+            let camera = pinhole.camera(Point3::zero(), Point3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0), aspect, 10.0);
+            let d = camera.get_ray(0.5, 1.0).unwrap().direction;
+            2.0 * d.y.atan2(-d.z).to_degrees()
+        };
+        assert!((top_angle(1.5) - settings.vfov()).abs() < 1e-6);
+        assert!((top_angle(0.75) - settings.vfov()).abs() < 1e-6);
+        assert!((top_angle(3.0) - 2.0 * 0.12f64.atan().to_degrees()).abs() < 1e-6);
+    }
+}
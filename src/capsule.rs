@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::constants::PI;
+use crate::hittable::*;
+use crate::poly::solve_quadratic;
+use crate::ray::Ray;
+use crate::vec3::*;
+use crate::material::*;
+
+// Cylinder between `p0` and `p1` with hemispherical ends.
+#[derive(Clone)]
+pub struct Capsule {
+    pub p0: Point3<f64>,
+    pub p1: Point3<f64>,
+    pub radius: f64,
+    pub mat_ptr: Arc<dyn Material>
+}
+
+impl Capsule {
+    pub fn new(p0: Point3<f64>, p1: Point3<f64>, radius: f64, mat_ptr: Arc<dyn Material>) -> Self { Self { p0, p1, radius, mat_ptr } }
+}
+
+impl Hittable for Capsule {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let axis = self.p1 - self.p0;
+        let length = axis.length();
+        let a = axis / length;
+        let (tangent, bitangent) = orthonormal_basis(a);
+        let radius_squared = self.radius * self.radius;
+
+        let mut closest: Option<(f64, Vec3<f64>)> = None;
+        let mut consider = |t: f64, normal: Vec3<f64>| {
+            if t >= t_min && t <= t_max && closest.is_none_or(|c| t < c.0) {
+                closest = Some((t, normal));
+            }
+        };
+
+        // Side, valid only between the two end points.
+        let oc = r.origin - self.p0;
+        let d_perp = r.direction - r.direction.dot(a) * a;
+        let oc_perp = oc - oc.dot(a) * a;
+        for t in solve_quadratic(d_perp.length_squared(), 2.0 * d_perp.dot(oc_perp), oc_perp.length_squared() - radius_squared) {
+            let q = r.at(t) - self.p0;
+            let y = q.dot(a);
+            if (0.0..=length).contains(&y) {
+                consider(t, (q - y * a) / self.radius);
+            }
+        }
+
+        // End caps, each valid only beyond its own end point.
+        for (center, outward) in [(self.p0, -a), (self.p1, a)] {
+            let oc = r.origin - center;
+            for t in solve_quadratic(r.direction.length_squared(), 2.0 * oc.dot(r.direction), oc.length_squared() - radius_squared) {
+                let q = r.at(t) - center;
+                if q.dot(outward) >= 0.0 {
+                    consider(t, q / self.radius);
+                }
+            }
+        }
+
+        let Some((t, outward_normal)) = closest else { return false; };
+        rec.t = t;
+        rec.p = r.at(t);
+        // u: angle around the axis, v: position along the full length including caps.
+        let q = rec.p - self.p0;
+        let radial = q - q.dot(a) * a;
+        rec.u = (radial.dot(bitangent).atan2(radial.dot(tangent)) + PI) / (2.0 * PI);
+        rec.v = ((q.dot(a) + self.radius) / (length + 2.0 * self.radius)).clamp(0.0, 1.0);
+        rec.set_face_normal(&r, &outward_normal);
+        // Along v the point runs up the axis, and over the caps also in
+        // towards it; both are undefined at the tips.
+        let s = q.dot(a) - q.dot(a).clamp(0.0, length);
+        let rho_squared = radial.length_squared();
+        if rho_squared > 1e-18 {
+            rec.dpdu = 2.0 * PI * azimuth_derivative(radial, tangent, bitangent);
+            rec.dpdv = (length + 2.0 * self.radius) * (a - (s / rho_squared) * radial);
+        }
+        rec.mat_ptr = self.mat_ptr.clone();
+
+        true
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut Aabb) -> bool {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        *output_box = surrounding_box(&Aabb::new(self.p0 - r, self.p0 + r), &Aabb::new(self.p1 - r, self.p1 + r));
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Capsule;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::hittable::test::check_surface_derivatives;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn hits_side_caps_and_inside() {
+        let capsule = Capsule::new(Point3::zero(), Point3::new(0.0, 2.0, 0.0), 0.5, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        let mut rec = HitRecord::zero();
+
+        // Side, at the middle of the full length.
+        let r = Ray::new(Point3::new(5.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(capsule.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.5).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.v - 0.5).abs() < 1e-9);
+        assert!((0.0..=1.0).contains(&rec.u));
+
+        // Top of the upper cap, at the very end of v.
+        let r = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(capsule.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.5).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!((rec.v - 1.0).abs() < 1e-9);
+
+        // Off axis, the cap normal points away from the end point.
+        let r = Ray::new(Point3::new(0.3, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(capsule.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.p.y - 2.4).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(0.6, 0.8, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+
+        // From inside, the side is a back face.
+        let r = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(capsule.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 0.5).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(!rec.front_face);
+
+        // Just wider than the radius.
+        let r = Ray::new(Point3::new(0.4, 5.0, 0.4), Vec3::new(0.0, -1.0, 0.0));
+        assert!(!capsule.hit(r, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn derivatives_follow_uv() {
+        let capsule = Capsule::new(Point3::zero(), Point3::new(0.3, 2.0, 0.0), 0.5, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        check_surface_derivatives(&capsule, Ray::new(Point3::new(5.0, 1.0, 0.2), Vec3::new(-1.0, 0.0, 0.0)));
+        check_surface_derivatives(&capsule, Ray::new(Point3::new(0.5, 6.0, 0.2), Vec3::new(0.0, -1.0, 0.0)));
+        check_surface_derivatives(&capsule, Ray::new(Point3::new(0.2, -5.0, -0.1), Vec3::new(0.0, 1.0, 0.0)));
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::constants::PI;
+use crate::hittable::*;
+use crate::poly::solve_quadratic;
+use crate::ray::Ray;
+use crate::vec3::*;
+use crate::material::*;
+
+// Cone with its tip at `apex`, closed by a disk of `radius` around `base`.
+#[derive(Clone)]
+pub struct Cone {
+    pub apex: Point3<f64>,
+    pub base: Point3<f64>,
+    pub radius: f64,
+    pub mat_ptr: Arc<dyn Material>
+}
+
+impl Cone {
+    pub fn new(apex: Point3<f64>, base: Point3<f64>, radius: f64, mat_ptr: Arc<dyn Material>) -> Self { Self { apex, base, radius, mat_ptr } }
+}
+
+// Side or base hit: t, outward normal, u, v, dpdu and dpdv.
+type Candidate = (f64, Vec3<f64>, f64, f64, Vec3<f64>, Vec3<f64>);
+
+impl Hittable for Cone {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let axis = self.base - self.apex;
+        let height = axis.length();
+        let a = axis / height;
+        let (tangent, bitangent) = orthonormal_basis(a);
+        // Squared secant of the half angle.
+        let k = 1.0 + (self.radius / height).powi(2);
+
+        let mut closest: Option<Candidate> = None;
+        let mut consider = |t: f64, normal: Vec3<f64>, u: f64, v: f64, dpdu: Vec3<f64>, dpdv: Vec3<f64>| {
+            if t >= t_min && t <= t_max && closest.is_none_or(|c| t < c.0) {
+                closest = Some((t, normal, u, v, dpdu, dpdv));
+            }
+        };
+
+        // Points q = p - apex on the cone satisfy |q|^2 = k (q.a)^2 with q.a in [0, height].
+        let oc = r.origin - self.apex;
+        let (dd, oa) = (r.direction.dot(a), oc.dot(a));
+        let roots = solve_quadratic(
+            r.direction.length_squared() - k * dd * dd,
+            2.0 * (r.direction.dot(oc) - k * dd * oa),
+            oc.length_squared() - k * oa * oa,
+        );
+        for t in roots {
+            let q = r.at(t) - self.apex;
+            let y = q.dot(a);
+            if y > 0.0 && y <= height {
+                let radial = q - y * a;
+                let normal = (q - k * y * a).unit_vector();
+                let u = (radial.dot(bitangent).atan2(radial.dot(tangent)) + PI) / (2.0 * PI);
+                // Moving along v slides the point along the line through the apex.
+                let dpdu = 2.0 * PI * azimuth_derivative(radial, tangent, bitangent);
+                consider(t, normal, u, y / height, dpdu, q * (height / y));
+            }
+        }
+
+        if dd.abs() > 1e-8 {
+            let t = (self.base - r.origin).dot(a) / dd;
+            let q = r.at(t) - self.base;
+            if q.length_squared() <= self.radius * self.radius {
+                let u = (q.dot(bitangent).atan2(q.dot(tangent)) + PI) / (2.0 * PI);
+                let dpdu = 2.0 * PI * azimuth_derivative(q, tangent, bitangent);
+                let dpdv = if q.near_zero() { Vec3::zero() } else { self.radius * q.unit_vector() };
+                consider(t, a, u, q.length() / self.radius, dpdu, dpdv);
+            }
+        }
+
+        let Some((t, outward_normal, u, v, dpdu, dpdv)) = closest else { return false; };
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.u = u;
+        rec.v = v;
+        rec.set_face_normal(&r, &outward_normal);
+        rec.dpdu = dpdu;
+        rec.dpdv = dpdv;
+        rec.mat_ptr = self.mat_ptr.clone();
+
+        true
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut Aabb) -> bool {
+        let base = disk_box(self.base, self.base - self.apex, self.radius);
+        *output_box = surrounding_box(&base, &Aabb::new(self.apex, self.apex));
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Cone;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::hittable::test::check_surface_derivatives;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn hits_side_base_and_inside() {
+        let cone = Cone::new(Point3::new(0.0, 2.0, 0.0), Point3::zero(), 1.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        let mut rec = HitRecord::zero();
+
+        // Side, halfway down from the apex where the radius is 0.5.
+        let r = Ray::new(Point3::new(5.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cone.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.5).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(2.0, 1.0, 0.0) / 5.0f64.sqrt()).length() < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.v - 0.5).abs() < 1e-9);
+        assert!((0.0..=1.0).contains(&rec.u));
+
+        // Base cap from below.
+        let r = Ray::new(Point3::new(0.2, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert!(cone.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 3.0).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(0.0, -1.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.v - 0.2).abs() < 1e-9);
+
+        // From inside, the side is a back face.
+        let r = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cone.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 0.75).abs() < 1e-9);
+        assert!(!rec.front_face);
+        assert!(rec.normal.dot(r.direction) < 0.0);
+
+        // The mirror image nappe above the apex is not part of the cone.
+        let r = Ray::new(Point3::new(5.0, 3.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!cone.hit(r, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn derivatives_follow_uv() {
+        let cone = Cone::new(Point3::new(0.0, 2.0, 0.0), Point3::new(0.3, 0.0, 0.2), 1.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        check_surface_derivatives(&cone, Ray::new(Point3::new(5.0, 1.0, 1.0), Vec3::new(-1.0, 0.0, -0.2)));
+        check_surface_derivatives(&cone, Ray::new(Point3::new(0.6, -5.0, 0.5), Vec3::new(0.0, 1.0, 0.0)));
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::color::Color;
+use crate::constants::INFINITY;
+use crate::hittable::*;
+use crate::material::*;
+use crate::ray::Ray;
+use crate::utilities::rand_double;
+use crate::vec3::Vec3;
+
+// Volume of uniform density filling a closed boundary, e.g. fog or smoke.
+// Rays scatter inside at an exponentially distributed distance.
+#[derive(Clone)]
+pub struct ConstantMedium {
+    pub boundary: Arc<dyn Hittable>,
+    pub neg_inv_density: f64,
+    pub phase_function: Arc<dyn Material>
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Arc<dyn Hittable>, density: f64, albedo: Color<f64>) -> Self {
+        Self::with_phase(boundary, density, Arc::new(Isotropic::new(&albedo)))
+    }
+
+    pub fn with_phase(boundary: Arc<dyn Hittable>, density: f64, phase_function: Arc<dyn Material>) -> Self {
+        Self { boundary, neg_inv_density: -1.0 / density, phase_function }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let mut rec1 = HitRecord::zero();
+        let mut rec2 = HitRecord::zero();
+
+        if !self.boundary.hit(r, -INFINITY, INFINITY, &mut rec1) {
+            return false;
+        }
+        if !self.boundary.hit(r, rec1.t + 0.0001, INFINITY, &mut rec2) {
+            return false;
+        }
+
+        rec1.t = rec1.t.max(t_min);
+        rec2.t = rec2.t.min(t_max);
+        if rec1.t >= rec2.t {
+            return false;
+        }
+        rec1.t = rec1.t.max(0.0);
+
+        let ray_length = r.direction.length();
+        let distance_inside_boundary = (rec2.t - rec1.t) * ray_length;
+        let hit_distance = self.neg_inv_density * rand_double().ln();
+
+        if hit_distance > distance_inside_boundary {
+            return false;
+        }
+
+        rec.t = rec1.t + hit_distance / ray_length;
+        rec.p = r.at(rec.t);
+
+        // Arbitrary; a volume has no surface orientation.
+        rec.normal = Vec3::new(1.0, 0.0, 0.0);
+        rec.geometric_normal = rec.normal;
+        rec.front_face = true;
+        rec.u = 0.0;
+        rec.v = 0.0;
+        rec.mat_ptr = self.phase_function.clone();
+
+        true
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        self.boundary.bounding_box(time0, time1, output_box)
+    }
+}
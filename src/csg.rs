@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::constants::INFINITY;
+use crate::hittable::*;
+use crate::ray::Ray;
+use crate::vec3::Point3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl Operation {
+    fn inside(&self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            Operation::Union => in_a || in_b,
+            Operation::Intersection => in_a && in_b,
+            Operation::Difference => in_a && !in_b,
+        }
+    }
+}
+
+// Combines the inside spans of both operands with a boolean operation by
+// sweeping over their boundaries in order along the ray.
+fn combine(op: Operation, a: &dyn Hittable, b: &dyn Hittable, r: Ray<f64>, spans: &mut Vec<Span>) {
+    let mut spans_a = Vec::new();
+    let mut spans_b = Vec::new();
+    a.spans(r, &mut spans_a);
+    b.spans(r, &mut spans_b);
+
+    // (boundary, entering, from a)
+    let mut events: Vec<(&HitRecord<f64>, bool, bool)> = Vec::new();
+    for (list, from_a) in [(&spans_a, true), (&spans_b, false)] {
+        for span in list.iter() {
+            events.push((&span.enter, true, from_a));
+            events.push((&span.exit, false, from_a));
+        }
+    }
+    events.sort_by(|x, y| x.0.t.total_cmp(&y.0.t));
+
+    let (mut in_a, mut in_b) = (false, false);
+    let mut enter: Option<HitRecord<f64>> = None;
+    for (rec, entering, from_a) in events {
+        let was_inside = op.inside(in_a, in_b);
+        if from_a { in_a = entering } else { in_b = entering }
+        let is_inside = op.inside(in_a, in_b);
+
+        // Normals already face against the ray, so a boundary only needs its
+        // front_face flag set to whether it enters the result.
+        if !was_inside && is_inside {
+            let mut rec = rec.clone();
+            rec.front_face = true;
+            enter = Some(rec);
+        } else if was_inside && !is_inside {
+            let mut exit = rec.clone();
+            exit.front_face = false;
+            let enter = enter.take().unwrap_or_else(|| Span::unbounded(-INFINITY));
+            spans.push(Span { enter, exit });
+        }
+    }
+}
+
+fn hit_spans(op: Operation, a: &dyn Hittable, b: &dyn Hittable, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+    let mut spans = Vec::new();
+    combine(op, a, b, r, &mut spans);
+
+    // Open operands leave spans running off to infinity, which have nothing
+    // to hit at that end.
+    let boundary = spans.iter()
+        .flat_map(|s| [&s.enter, &s.exit])
+        .find(|b| b.t.is_finite() && b.t >= t_min && b.t <= t_max);
+    match boundary {
+        Some(b) => {
+            *rec = b.clone();
+            true
+        }
+        None => false,
+    }
+}
+
+// Points inside either operand.
+#[derive(Clone)]
+pub struct Union {
+    pub a: Arc<dyn Hittable>,
+    pub b: Arc<dyn Hittable>
+}
+
+impl Union {
+    pub fn new(a: Arc<dyn Hittable>, b: Arc<dyn Hittable>) -> Self { Self { a, b } }
+}
+
+impl Hittable for Union {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        hit_spans(Operation::Union, self.a.as_ref(), self.b.as_ref(), r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        let (mut box_a, mut box_b) = (Aabb::zero(), Aabb::zero());
+        if !self.a.bounding_box(time0, time1, &mut box_a) || !self.b.bounding_box(time0, time1, &mut box_b) {
+            return false;
+        }
+        *output_box = surrounding_box(&box_a, &box_b);
+        true
+    }
+
+    fn spans(&self, r: Ray<f64>, spans: &mut Vec<Span>) {
+        combine(Operation::Union, self.a.as_ref(), self.b.as_ref(), r, spans)
+    }
+}
+
+// Points inside both operands, e.g. a lens from two overlapping spheres.
+#[derive(Clone)]
+pub struct Intersection {
+    pub a: Arc<dyn Hittable>,
+    pub b: Arc<dyn Hittable>
+}
+
+impl Intersection {
+    pub fn new(a: Arc<dyn Hittable>, b: Arc<dyn Hittable>) -> Self { Self { a, b } }
+}
+
+impl Hittable for Intersection {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        hit_spans(Operation::Intersection, self.a.as_ref(), self.b.as_ref(), r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        let (mut box_a, mut box_b) = (Aabb::zero(), Aabb::zero());
+        match (self.a.bounding_box(time0, time1, &mut box_a), self.b.bounding_box(time0, time1, &mut box_b)) {
+            (true, true) => *output_box = overlap(&box_a, &box_b),
+            (true, false) => *output_box = box_a,
+            (false, true) => *output_box = box_b,
+            (false, false) => return false,
+        }
+        true
+    }
+
+    fn spans(&self, r: Ray<f64>, spans: &mut Vec<Span>) {
+        combine(Operation::Intersection, self.a.as_ref(), self.b.as_ref(), r, spans)
+    }
+}
+
+// Points inside `a` but not inside `b`.
+#[derive(Clone)]
+pub struct Difference {
+    pub a: Arc<dyn Hittable>,
+    pub b: Arc<dyn Hittable>
+}
+
+impl Difference {
+    pub fn new(a: Arc<dyn Hittable>, b: Arc<dyn Hittable>) -> Self { Self { a, b } }
+}
+
+impl Hittable for Difference {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        hit_spans(Operation::Difference, self.a.as_ref(), self.b.as_ref(), r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        self.a.bounding_box(time0, time1, output_box)
+    }
+
+    fn spans(&self, r: Ray<f64>, spans: &mut Vec<Span>) {
+        combine(Operation::Difference, self.a.as_ref(), self.b.as_ref(), r, spans)
+    }
+}
+
+fn overlap(box0: &Aabb, box1: &Aabb) -> Aabb {
+    Aabb::new(
+        Point3::new(box0.minimum.x.max(box1.minimum.x), box0.minimum.y.max(box1.minimum.y), box0.minimum.z.max(box1.minimum.z)),
+        Point3::new(box0.maximum.x.min(box1.maximum.x), box0.maximum.y.min(box1.maximum.y), box0.maximum.z.min(box1.maximum.z)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::plane::Plane;
+    use crate::sphere::Sphere;
+    use crate::vec3::*;
+
+    fn sphere(x: f64, radius: f64) -> Arc<dyn Hittable> {
+        Arc::new(Sphere::new(Point3::new(x, 0.0, 0.0), radius, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)))))
+    }
+
+    #[test]
+    fn lens_and_drilled_sphere() {
+        let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let mut rec = HitRecord::zero();
+
+        // Lens between x = -0.5 and x = 0.5.
+        let lens = Intersection::new(sphere(-0.5, 1.0), sphere(0.5, 1.0));
+        assert!(lens.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.5).abs() < 1e-9 && rec.front_face);
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+
+        // Starting inside the lens, the next boundary is its far side.
+        let inside = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+        assert!(lens.hit(inside, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 0.5).abs() < 1e-9 && !rec.front_face);
+
+        // Unit sphere with a bite taken out of its left side: the ray enters
+        // through the inside of the cutter, whose normal must face the ray.
+        let bitten = Difference::new(sphere(0.0, 1.0), sphere(-1.5, 1.0));
+        assert!(bitten.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.5).abs() < 1e-9 && rec.front_face);
+        assert!(rec.normal.dot(r.direction) < 0.0);
+
+        let both = Union::new(sphere(-0.5, 1.0), sphere(0.5, 1.0));
+        let mut spans = Vec::new();
+        both.spans(r, &mut spans);
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.t - 3.5).abs() < 1e-9);
+        assert!((spans[0].exit.t - 6.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn open_operands_never_hit_at_infinity() {
+        // Past the face of a half space, the union only ends at infinity.
+        let gray = Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)));
+        let half_space = Arc::new(Plane::new(Point3::zero(), Vec3::new(-1.0, 0.0, 0.0), gray));
+        let union = Union::new(half_space, sphere(-5.0, 1.0));
+        let r = Ray::new(Point3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let mut rec = HitRecord::zero();
+        assert!(!union.hit(r, 0.001, f64::INFINITY, &mut rec));
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::hittable::*;
+use crate::hittable_list::*;
+use crate::quad::*;
+use crate::vec3::*;
+use crate::material::*;
+
+// A box made of six quads with outward facing normals.
+pub struct Cuboid {
+    pub sides: HittableList<'static>
+}
+
+impl Cuboid {
+    // Axis-aligned box spanning two opposite corners.
+    pub fn new(p0: Point3<f64>, p1: Point3<f64>, mat_ptr: Arc<dyn Material>) -> Self {
+        let b = Aabb::from_points(p0, p1);
+        let center = 0.5 * (b.minimum + b.maximum);
+        let half_extents = 0.5 * (b.maximum - b.minimum);
+        Self::oriented(center, half_extents, Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), mat_ptr)
+    }
+
+    // Box centered at `center`, with its local x axis along `x_axis` and its
+    // local y axis as close to `y_axis` as orthogonality allows.
+    pub fn oriented(center: Point3<f64>, half_extents: Vec3<f64>, x_axis: Vec3<f64>, y_axis: Vec3<f64>, mat_ptr: Arc<dyn Material>) -> Self {
+        let x = x_axis.unit_vector();
+        let z = x.cross(y_axis).unit_vector();
+        let y = z.cross(x);
+        let axes = [x, y, z];
+        let extents = half_extents.to_vec();
+
+        let mut sides = HittableList::new();
+        for i in 0..3 {
+            let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+            let u = 2.0 * extents[j] * axes[j];
+            let v = 2.0 * extents[k] * axes[k];
+            for sign in [1.0, -1.0] {
+                let face_center = center + sign * extents[i] * axes[i];
+                // u x v points along +axes[i]; swap the edges for the far side.
+                let (u, v) = if sign > 0.0 { (u, v) } else { (v, u) };
+                sides.add(Quad::new(face_center - 0.5 * u - 0.5 * v, u, v, mat_ptr.clone()));
+            }
+        }
+
+        Self { sides }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, r: crate::ray::Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        self.sides.hit(r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        self.sides.bounding_box(time0, time1, output_box)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Cuboid;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn faces_point_outwards() {
+        let gray = Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)));
+        let cuboid = Cuboid::new(Point3::zero(), Point3::new(1.0, 2.0, 3.0), gray.clone());
+        let mut rec = HitRecord::zero();
+
+        // The -x face, with u along z and v along y.
+        let r = Ray::new(Point3::new(-1.0, 0.5, 1.5), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cuboid.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 1.0).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.u - 0.5).abs() < 1e-9);
+        assert!((rec.v - 0.25).abs() < 1e-9);
+
+        // The +y face from above.
+        let r = Ray::new(Point3::new(0.5, 5.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(cuboid.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+
+        // From the center, the +z face is a back face.
+        let r = Ray::new(Point3::new(0.5, 1.0, 1.5), Vec3::new(0.0, 0.0, 1.0));
+        assert!(cuboid.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 1.5).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+        assert!(!rec.front_face);
+
+        // A unit box turned 45 degrees about z.
+        let turned = Cuboid::oriented(Point3::zero(), Vec3::new(1.0, 1.0, 1.0), Vec3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, 1.0, 0.0), gray);
+        let r = Ray::new(Point3::new(5.0, 0.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(turned.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.p.x - (2.0f64.sqrt() - 0.5)).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(1.0, 1.0, 0.0) / 2.0f64.sqrt()).length() < 1e-9);
+        assert!(rec.front_face);
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::constants::PI;
+use crate::hittable::*;
+use crate::poly::solve_quadratic;
+use crate::ray::Ray;
+use crate::vec3::*;
+use crate::material::*;
+
+// Capped cylinder between the centers of its two end caps.
+#[derive(Clone)]
+pub struct Cylinder {
+    pub base: Point3<f64>,
+    pub top: Point3<f64>,
+    pub radius: f64,
+    pub mat_ptr: Arc<dyn Material>
+}
+
+impl Cylinder {
+    pub fn new(base: Point3<f64>, top: Point3<f64>, radius: f64, mat_ptr: Arc<dyn Material>) -> Self { Self { base, top, radius, mat_ptr } }
+}
+
+// Side or cap hit: t, outward normal, u, v, dpdu and dpdv.
+type Candidate = (f64, Vec3<f64>, f64, f64, Vec3<f64>, Vec3<f64>);
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let axis = self.top - self.base;
+        let height = axis.length();
+        let a = axis / height;
+        let (tangent, bitangent) = orthonormal_basis(a);
+
+        let oc = r.origin - self.base;
+        let d_perp = r.direction - r.direction.dot(a) * a;
+        let oc_perp = oc - oc.dot(a) * a;
+
+        let mut closest: Option<Candidate> = None;
+        let mut consider = |t: f64, normal: Vec3<f64>, u: f64, v: f64, dpdu: Vec3<f64>, dpdv: Vec3<f64>| {
+            if t >= t_min && t <= t_max && closest.is_none_or(|c| t < c.0) {
+                closest = Some((t, normal, u, v, dpdu, dpdv));
+            }
+        };
+
+        let roots = solve_quadratic(d_perp.length_squared(), 2.0 * d_perp.dot(oc_perp), oc_perp.length_squared() - self.radius * self.radius);
+        for t in roots {
+            let q = r.at(t) - self.base;
+            let y = q.dot(a);
+            if (0.0..=height).contains(&y) {
+                let radial = q - y * a;
+                let u = (radial.dot(bitangent).atan2(radial.dot(tangent)) + PI) / (2.0 * PI);
+                let dpdu = 2.0 * PI * azimuth_derivative(radial, tangent, bitangent);
+                consider(t, radial / self.radius, u, y / height, dpdu, axis);
+            }
+        }
+
+        let denom = r.direction.dot(a);
+        if denom.abs() > 1e-8 {
+            for (center, normal) in [(self.base, -a), (self.top, a)] {
+                let t = (center - r.origin).dot(a) / denom;
+                let q = r.at(t) - center;
+                if q.length_squared() <= self.radius * self.radius {
+                    let u = (q.dot(bitangent).atan2(q.dot(tangent)) + PI) / (2.0 * PI);
+                    let dpdu = 2.0 * PI * azimuth_derivative(q, tangent, bitangent);
+                    let dpdv = if q.near_zero() { Vec3::zero() } else { self.radius * q.unit_vector() };
+                    consider(t, normal, u, q.length() / self.radius, dpdu, dpdv);
+                }
+            }
+        }
+
+        let Some((t, outward_normal, u, v, dpdu, dpdv)) = closest else { return false; };
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.u = u;
+        rec.v = v;
+        rec.set_face_normal(&r, &outward_normal);
+        rec.dpdu = dpdu;
+        rec.dpdv = dpdv;
+        rec.mat_ptr = self.mat_ptr.clone();
+
+        true
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut Aabb) -> bool {
+        let a = self.top - self.base;
+        *output_box = surrounding_box(&disk_box(self.base, a, self.radius), &disk_box(self.top, a, self.radius));
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Cylinder;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::hittable::test::check_surface_derivatives;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn hits_side_caps_and_inside() {
+        let cylinder = Cylinder::new(Point3::zero(), Point3::new(0.0, 2.0, 0.0), 1.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        let mut rec = HitRecord::zero();
+
+        // Side, halfway up.
+        let r = Ray::new(Point3::new(5.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cylinder.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.0).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.v - 0.5).abs() < 1e-9);
+        let u_x = rec.u;
+
+        // A quarter turn around the axis moves u by a quarter.
+        let r = Ray::new(Point3::new(0.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(cylinder.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+        let du = (rec.u - u_x).rem_euclid(1.0);
+        assert!((du - 0.25).abs() < 1e-9 || (du - 0.75).abs() < 1e-9);
+
+        // Top cap from above, v running out from the center.
+        let r = Ray::new(Point3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(cylinder.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 3.0).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.v - 0.5).abs() < 1e-9);
+
+        // Bottom cap from below.
+        let r = Ray::new(Point3::new(0.0, -3.0, 0.5), Vec3::new(0.0, 1.0, 0.0));
+        assert!(cylinder.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.normal - Vec3::new(0.0, -1.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+
+        // From inside, the side is a back face.
+        let r = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cylinder.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 1.0).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(!rec.front_face);
+
+        // Past the top.
+        let r = Ray::new(Point3::new(5.0, 3.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!cylinder.hit(r, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn derivatives_follow_uv() {
+        let cylinder = Cylinder::new(Point3::new(0.0, -1.0, 0.5), Point3::new(0.5, 2.0, 0.0), 1.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        check_surface_derivatives(&cylinder, Ray::new(Point3::new(5.0, 0.5, 1.0), Vec3::new(-1.0, 0.1, -0.2)));
+        check_surface_derivatives(&cylinder, Ray::new(Point3::new(0.7, 6.0, 0.4), Vec3::new(0.0, -1.0, 0.0)));
+        check_surface_derivatives(&cylinder, Ray::new(Point3::new(-0.3, -5.0, 0.2), Vec3::new(0.0, 1.0, 0.0)));
+    }
+}
@@ -0,0 +1,139 @@
+use std::io;
+
+use crate::color::*;
+use crate::film::*;
+
+// Edge-avoiding à-trous wavelet filter (Dammertz et al. 2010). Each pass
+// applies a 5x5 B3-spline kernel with holes of growing size, weighting
+// neighbours by how similar their color, albedo, normal and position are.
+#[derive(Debug, Clone, Copy)]
+pub struct Denoiser {
+    pub iterations: usize,
+    pub sigma_color: f64,
+    pub sigma_albedo: f64,
+    pub sigma_normal: f64,
+    pub sigma_position: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self { iterations: 5, sigma_color: 0.6, sigma_albedo: 0.1, sigma_normal: 0.3, sigma_position: 0.3 }
+    }
+}
+
+const KERNEL: [f64; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+impl Denoiser {
+    // Filters the `beauty` channel of a resolved film into `output`, guided by
+    // its `albedo`, `normal` and `position` channels.
+    pub fn apply(&self, film: &mut Film, beauty: &str, output: &str) -> io::Result<()> {
+        let guide = |name: &str| {
+            film.channel(name)
+                .map(|c| c.pixels.clone())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("denoising needs the {} channel", name)))
+        };
+        let color = guide(beauty)?;
+        let albedo = guide("albedo")?;
+        let normal = guide("normal")?;
+        let position = guide("position")?;
+
+        // Filter irradiance rather than radiance so texture detail carried by
+        // the albedo is not blurred away.
+        let mut irradiance: Vec<Color<f64>> = color.iter().zip(albedo.iter()).map(|(c, a)| demodulate(*c, *a)).collect();
+
+        let (width, height) = (film.width as i64, film.height as i64);
+        for iteration in 0..self.iterations {
+            let step = 1i64 << iteration;
+            // Later passes see smoother input, so tolerate less color difference.
+            let sigma_color = self.sigma_color / (1 << iteration) as f64;
+            let mut filtered = irradiance.clone();
+
+            for y in 0..height {
+                for x in 0..width {
+                    let p = (y * width + x) as usize;
+                    let mut sum = Color::zero();
+                    let mut weight_sum = 0.0;
+
+                    for (j, ky) in KERNEL.iter().enumerate() {
+                        for (i, kx) in KERNEL.iter().enumerate() {
+                            let qx = x + (i as i64 - 2) * step;
+                            let qy = y + (j as i64 - 2) * step;
+                            if qx < 0 || qy < 0 || qx >= width || qy >= height {
+                                continue;
+                            }
+                            let q = (qy * width + qx) as usize;
+
+                            let weight = kx * ky
+                                * edge_stop(irradiance[p] - irradiance[q], sigma_color)
+                                * edge_stop(albedo[p] - albedo[q], self.sigma_albedo)
+                                * edge_stop(normal[p] - normal[q], self.sigma_normal)
+                                * edge_stop(position[p] - position[q], self.sigma_position);
+                            sum += weight * irradiance[q];
+                            weight_sum += weight;
+                        }
+                    }
+
+                    filtered[p] = if weight_sum > 0.0 { sum / weight_sum } else { irradiance[p] };
+                }
+            }
+            irradiance = filtered;
+        }
+
+        film.channel_mut(output).pixels = irradiance.iter().zip(albedo.iter()).map(|(e, a)| remodulate(*e, *a)).collect();
+        Ok(())
+    }
+}
+
+#[inline]
+fn edge_stop(difference: Color<f64>, sigma: f64) -> f64 {
+    (-difference.length_squared() / (sigma * sigma)).exp()
+}
+
+// Albedo components below this are left modulated to avoid blowing up noise.
+const MIN_ALBEDO: f64 = 0.01;
+
+fn demodulate(color: Color<f64>, albedo: Color<f64>) -> Color<f64> {
+    let d = |c: f64, a: f64| if a > MIN_ALBEDO { c / a } else { c };
+    Color::new(d(color.x, albedo.x), d(color.y, albedo.y), d(color.z, albedo.z))
+}
+
+fn remodulate(irradiance: Color<f64>, albedo: Color<f64>) -> Color<f64> {
+    let r = |e: f64, a: f64| if a > MIN_ALBEDO { e * a } else { e };
+    Color::new(r(irradiance.x, albedo.x), r(irradiance.y, albedo.y), r(irradiance.z, albedo.z))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Denoiser;
+    use crate::color::Color;
+    use crate::film::Film;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn smooths_noise_but_keeps_normal_edges() {
+        let (width, height) = (16, 8);
+        let mut film = Film::new(width, height);
+        let mut rng = StdRng::seed_from_u64(7);
+        for y in 0..height {
+            for x in 0..width {
+                let left = x < width / 2;
+                let level = if left { 0.2 } else { 0.8 };
+                let noise = 0.2 * (rng.gen::<f64>() - 0.5);
+                film.add("beauty", x, y, Color::new(level + noise, level + noise, level + noise));
+                film.add("albedo", x, y, Color::new(1.0, 1.0, 1.0));
+                film.add("normal", x, y, if left { Color::new(1.0, 0.0, 0.0) } else { Color::new(0.0, 1.0, 0.0) });
+                film.add("position", x, y, Color::zero());
+            }
+        }
+
+        Denoiser::default().apply(&mut film, "beauty", "denoised").unwrap();
+
+        for y in 0..height {
+            for x in 0..width {
+                let expected = if x < width / 2 { 0.2 } else { 0.8 };
+                assert!((film.get("denoised", x, y).x - expected).abs() < 0.1);
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::constants::PI;
+use crate::hittable::*;
+use crate::vec3::*;
+use crate::material::*;
+use crate::utilities::rand_double;
+
+#[derive(Clone)]
+pub struct Disk {
+    pub center: Point3<f64>,
+    pub normal: Vec3<f64>,
+    pub radius: f64,
+    pub mat_ptr: Arc<dyn Material>
+}
+
+impl Disk {
+    pub fn new(center: Point3<f64>, normal: Vec3<f64>, radius: f64, mat_ptr: Arc<dyn Material>) -> Self {
+        Self { center, normal: normal.unit_vector(), radius, mat_ptr }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: crate::ray::Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let denom = self.normal.dot(r.direction);
+        if denom.abs() < 1e-8 { return false; }
+
+        let t = (self.center - r.origin).dot(self.normal) / denom;
+        if t < t_min || t_max < t { return false; }
+
+        let p = r.at(t);
+        let d = p - self.center;
+        let dist_squared = d.length_squared();
+        if dist_squared > self.radius * self.radius { return false; }
+
+        // u: angle around the normal, v: distance from the center.
+        let (tangent, bitangent) = orthonormal_basis(self.normal);
+        rec.t = t;
+        rec.p = p;
+        rec.u = (d.dot(bitangent).atan2(d.dot(tangent)) + PI) / (2.0 * PI);
+        rec.v = dist_squared.sqrt() / self.radius;
+        rec.set_face_normal(&r, &self.normal);
+        if dist_squared > 0.0 {
+            rec.dpdu = 2.0 * PI * azimuth_derivative(d, tangent, bitangent);
+            rec.dpdv = self.radius * d.unit_vector();
+        }
+        rec.mat_ptr = self.mat_ptr.clone();
+
+        true
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn random(&self, origin: &Point3<f64>) -> Vec3<f64> {
+        let (tangent, bitangent) = orthonormal_basis(self.normal);
+        let r = self.radius * rand_double().sqrt();
+        let phi = 2.0 * PI * rand_double();
+        let p = self.center + r * phi.cos() * tangent + r * phi.sin() * bitangent;
+        (p - *origin).unit_vector()
+    }
+
+    fn pdf_value(&self, origin: &Point3<f64>, direction: &Vec3<f64>) -> f64 {
+        area_pdf(self, origin, direction)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut Aabb) -> bool {
+        *output_box = disk_box(self.center, self.normal, self.radius).pad(1e-4);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Disk;
+    use crate::aabb::Aabb;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::hittable::test::check_surface_derivatives;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn hits_inside_radius_only() {
+        let disk = Disk::new(Point3::new(1.0, 2.0, 3.0), Vec3::new(0.0, 0.0, 3.0), 2.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        let mut rec = HitRecord::zero();
+
+        // Halfway out from the center, seen from the front.
+        let r = Ray::new(Point3::new(2.0, 2.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(disk.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.0).abs() < 1e-12);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+        assert!(rec.front_face);
+        assert!((rec.v - 0.5).abs() < 1e-12);
+        assert!((0.0..=1.0).contains(&rec.u));
+
+        // The same point from behind.
+        let r = Ray::new(Point3::new(2.0, 2.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(disk.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-12);
+        assert!(!rec.front_face);
+
+        // Outside the radius and parallel to the disk.
+        assert!(!disk.hit(Ray::new(Point3::new(3.5, 2.0, 5.0), Vec3::new(0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut rec));
+        assert!(!disk.hit(Ray::new(Point3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut rec));
+
+        // The box is flat in z and just covers the rim in x and y.
+        let mut b = Aabb::zero();
+        assert!(disk.bounding_box(0.0, 1.0, &mut b));
+        assert!((b.minimum - Point3::new(-1.0, 0.0, 3.0)).length() < 1e-3);
+        assert!((b.maximum - Point3::new(3.0, 4.0, 3.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn derivatives_follow_uv() {
+        let disk = Disk::new(Point3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 1.0, 3.0), 2.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        check_surface_derivatives(&disk, Ray::new(Point3::new(1.7, 1.6, 6.0), Vec3::new(0.0, 0.1, -1.0)));
+        check_surface_derivatives(&disk, Ray::new(Point3::new(0.5, 2.5, 0.0), Vec3::new(0.0, 0.0, 1.0)));
+    }
+}
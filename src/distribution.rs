@@ -0,0 +1,158 @@
+// Piecewise constant distribution over [0, 1) with one bucket per value of
+// `func`, sampled by inverting its CDF.
+#[derive(Debug, Clone)]
+pub struct Distribution1D {
+    pub func: Vec<f64>,
+    pub cdf: Vec<f64>,
+    // Integral of `func` over [0, 1).
+    pub func_int: f64
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f64>) -> Self {
+        let n = func.len();
+        let mut cdf = vec![0.0; n + 1];
+        for i in 0..n {
+            cdf[i + 1] = cdf[i] + func[i].max(0.0) / n as f64;
+        }
+        let func_int = cdf[n];
+        for (i, c) in cdf.iter_mut().enumerate().skip(1) {
+            // An all-zero function falls back to uniform.
+            *c = if func_int > 0.0 { *c / func_int } else { i as f64 / n as f64 };
+        }
+        Self { func, cdf, func_int }
+    }
+
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+
+    // Maps a uniform `u` to (x, pdf, bucket).
+    pub fn sample_continuous(&self, u: f64) -> (f64, f64, usize) {
+        let offset = self.cdf.partition_point(|c| *c <= u).clamp(1, self.count()) - 1;
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let du = if width > 0.0 { (u - self.cdf[offset]) / width } else { 0.0 };
+        let x = (offset as f64 + du) / self.count() as f64;
+        (x.min(1.0 - f64::EPSILON), self.pdf(x), offset)
+    }
+
+    pub fn pdf(&self, x: f64) -> f64 {
+        if self.func_int <= 0.0 {
+            return 1.0;
+        }
+        let offset = ((x * self.count() as f64) as usize).min(self.count() - 1);
+        self.func[offset].max(0.0) / self.func_int
+    }
+}
+
+// Piecewise constant distribution over [0, 1)^2, sampled as a marginal over
+// rows (v) and then a conditional along the chosen row (u).
+#[derive(Debug, Clone)]
+pub struct Distribution2D {
+    pub conditional: Vec<Distribution1D>,
+    pub marginal: Distribution1D
+}
+
+impl Distribution2D {
+    // `func` holds `height` rows of `width` values, row v = 0 first.
+    pub fn new(func: &[f64], width: usize, height: usize) -> Self {
+        let conditional: Vec<Distribution1D> = func.chunks(width).take(height).map(|row| Distribution1D::new(row.to_vec())).collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|c| c.func_int).collect());
+        Self { conditional, marginal }
+    }
+
+    // Maps two uniforms to ((u, v), pdf).
+    pub fn sample_continuous(&self, u0: f64, u1: f64) -> ((f64, f64), f64) {
+        let (v, pdf_v, row) = self.marginal.sample_continuous(u1);
+        let (u, pdf_u, _) = self.conditional[row].sample_continuous(u0);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    pub fn pdf(&self, u: f64, v: f64) -> f64 {
+        let row = ((v * self.marginal.count() as f64) as usize).min(self.marginal.count() - 1);
+        self.marginal.pdf(v) * self.conditional[row].pdf(u)
+    }
+}
+
+// Discrete distribution over `weights.len()` outcomes that samples in
+// constant time with Walker's alias method, built with Vose's algorithm.
+#[derive(Debug, Clone)]
+pub struct AliasTable {
+    // Chance of keeping each bucket rather than taking its alias.
+    pub probability: Vec<f64>,
+    pub alias: Vec<usize>,
+    pub pmf: Vec<f64>
+}
+
+impl AliasTable {
+    pub fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let total: f64 = weights.iter().map(|w| w.max(0.0)).sum();
+        // All-zero weights fall back to uniform, like Distribution1D.
+        let pmf: Vec<f64> = weights.iter().map(|w| if total > 0.0 { w.max(0.0) / total } else { 1.0 / n as f64 }).collect();
+
+        let mut probability = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+        let mut scaled: Vec<f64> = pmf.iter().map(|p| p * n as f64).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..n).partition(|i| scaled[*i] < 1.0);
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            probability[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Whatever remains is full up to rounding.
+        Self { probability, alias, pmf }
+    }
+
+    pub fn count(&self) -> usize {
+        self.pmf.len()
+    }
+
+    // Maps a uniform `u` to (outcome, pmf).
+    pub fn sample(&self, u: f64) -> (usize, f64) {
+        let scaled = u * self.count() as f64;
+        let i = (scaled as usize).min(self.count() - 1);
+        let index = if scaled - (i as f64) < self.probability[i] { i } else { self.alias[i] };
+        (index, self.pmf[index])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn samples_follow_the_function() {
+        let d = Distribution1D::new(vec![1.0, 3.0]);
+        assert!((d.func_int - 2.0).abs() < 1e-12);
+
+        // A quarter of the mass is in the first bucket.
+        let (x, pdf, offset) = d.sample_continuous(0.125);
+        assert!((x - 0.25).abs() < 1e-12 && (pdf - 0.5).abs() < 1e-12 && offset == 0);
+        let (x, pdf, offset) = d.sample_continuous(0.625);
+        assert!((x - 0.75).abs() < 1e-12 && (pdf - 1.5).abs() < 1e-12 && offset == 1);
+
+        let d2 = Distribution2D::new(&[0.0, 1.0, 1.0, 2.0], 2, 2);
+        let ((u, v), pdf) = d2.sample_continuous(0.5, 0.9);
+        assert!(u >= 0.5 && v >= 0.5);
+        assert!((pdf - d2.pdf(u, v)).abs() < 1e-12);
+        assert!((d2.pdf(0.75, 0.75) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn alias_table_matches_weights() {
+        let table = AliasTable::new(&[1.0, 0.0, 3.0, 4.0]);
+        let mut counts = [0; 4];
+        let n = 8000;
+        for i in 0..n {
+            let (index, pmf) = table.sample((i as f64 + 0.5) / n as f64);
+            assert_eq!(pmf, table.pmf[index]);
+            counts[index] += 1;
+        }
+        assert_eq!(counts, [1000, 0, 3000, 4000]);
+    }
+}
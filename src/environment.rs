@@ -0,0 +1,296 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::color::*;
+use crate::constants::PI;
+use crate::distribution::Distribution2D;
+use crate::utilities::{degrees_to_radians, rand_double};
+use crate::vec3::*;
+
+// Light arriving from infinitely far away, seen by rays that miss the world.
+pub trait Environment: Send + Sync {
+    fn radiance(&self, direction: &Vec3<f64>) -> Color<f64>;
+
+    // Unit direction towards the environment, chosen in proportion to its
+    // brightness, with its solid angle pdf. None when the environment can't
+    // be sampled, in which case it is only found by scattered rays.
+    fn sample(&self) -> Option<(Vec3<f64>, f64)> {
+        None
+    }
+
+    fn pdf(&self, _direction: &Vec3<f64>) -> f64 {
+        0.0
+    }
+}
+
+// The original white to light blue sky gradient.
+#[derive(Debug, Clone, Copy)]
+pub struct GradientSky {
+    pub horizon: Color<f64>,
+    pub zenith: Color<f64>
+}
+
+impl Default for GradientSky {
+    fn default() -> Self {
+        Self { horizon: Color::new(1.0, 1.0, 1.0), zenith: Color::new(0.5, 0.7, 1.0) }
+    }
+}
+
+impl GradientSky {
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.horizon = intensity * self.horizon;
+        self.zenith = intensity * self.zenith;
+        self
+    }
+}
+
+impl Environment for GradientSky {
+    fn radiance(&self, direction: &Vec3<f64>) -> Color<f64> {
+        let unit_direction = direction.unit_vector();
+        let t = 0.5 * (unit_direction.y + 1.0);
+        (1.0 - t) * self.horizon + t * self.zenith
+    }
+}
+
+// Latitude-longitude HDR image around the scene, with +y at the top row and
+// -z at the horizontal center. Importance sampled by pixel luminance.
+pub struct EnvironmentMap {
+    pub width: usize,
+    pub height: usize,
+    // Linear radiance, top row first.
+    pub pixels: Vec<Color<f64>>,
+    // Degrees about +y.
+    pub rotation: f64,
+    pub intensity: f64,
+    distribution: Distribution2D
+}
+
+impl EnvironmentMap {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color<f64>>) -> Self {
+        assert_eq!(pixels.len(), width * height, "pixel count does not match the image size");
+
+        // Rows near the poles cover less solid angle, hence the sin(theta).
+        let mut func = Vec::with_capacity(width * height);
+        for j in 0..height {
+            let sin_theta = (PI * (j as f64 + 0.5) / height as f64).sin();
+            func.extend(pixels[j * width..(j + 1) * width].iter().map(|c| luminance(c) * sin_theta));
+        }
+        let distribution = Distribution2D::new(&func, width, height);
+
+        Self { width, height, pixels, rotation: 0.0, intensity: 1.0, distribution }
+    }
+
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    // Radiance .hdr (RGBE) or portable float map, chosen by extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        let (width, height, pixels) = match path.extension().and_then(|e| e.to_str()) {
+            Some("pfm") => read_pfm(&bytes)?,
+            _ => read_rgbe(&bytes)?,
+        };
+        Ok(Self::new(width, height, pixels))
+    }
+
+    // Map coordinates in [0, 1)^2 for a world direction.
+    fn direction_to_uv(&self, direction: &Vec3<f64>) -> (f64, f64) {
+        let d = rotate_y(&direction.unit_vector(), -self.rotation);
+        let u = 0.5 + d.x.atan2(-d.z) / (2.0 * PI);
+        let v = d.y.clamp(-1.0, 1.0).acos() / PI;
+        (u.rem_euclid(1.0), v.clamp(0.0, 1.0))
+    }
+
+    fn uv_to_direction(&self, u: f64, v: f64) -> Vec3<f64> {
+        let (theta, phi) = (v * PI, (u - 0.5) * 2.0 * PI);
+        let d = Vec3::new(theta.sin() * phi.sin(), theta.cos(), -theta.sin() * phi.cos());
+        rotate_y(&d, self.rotation)
+    }
+
+    fn lookup(&self, u: f64, v: f64) -> Color<f64> {
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((v * self.height as f64) as usize).min(self.height - 1);
+        self.pixels[j * self.width + i]
+    }
+}
+
+impl Environment for EnvironmentMap {
+    fn radiance(&self, direction: &Vec3<f64>) -> Color<f64> {
+        let (u, v) = self.direction_to_uv(direction);
+        self.intensity * self.lookup(u, v)
+    }
+
+    fn sample(&self) -> Option<(Vec3<f64>, f64)> {
+        let ((u, v), map_pdf) = self.distribution.sample_continuous(rand_double(), rand_double());
+        let sin_theta = (v * PI).sin();
+        if map_pdf <= 0.0 || sin_theta <= 0.0 {
+            return None;
+        }
+        // Change of variables from the unit square to the sphere.
+        Some((self.uv_to_direction(u, v), map_pdf / (2.0 * PI * PI * sin_theta)))
+    }
+
+    fn pdf(&self, direction: &Vec3<f64>) -> f64 {
+        let (u, v) = self.direction_to_uv(direction);
+        let sin_theta = (v * PI).sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+pub fn luminance(c: &Color<f64>) -> f64 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+fn rotate_y(d: &Vec3<f64>, degrees: f64) -> Vec3<f64> {
+    let (sin, cos) = degrees_to_radians(degrees).sin_cos();
+    Vec3::new(cos * d.x + sin * d.z, d.y, -sin * d.x + cos * d.z)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+// Reads one newline terminated header line starting at `pos`.
+fn read_line<'a>(bytes: &'a [u8], pos: &mut usize) -> io::Result<&'a str> {
+    let start = *pos;
+    let end = bytes[start..].iter().position(|b| *b == b'\n').map(|i| start + i).ok_or_else(|| invalid("truncated header"))?;
+    *pos = end + 1;
+    std::str::from_utf8(&bytes[start..end]).map(|s| s.trim()).map_err(|_| invalid("header is not text"))
+}
+
+// Radiance RGBE, flat or with new style run length encoded scanlines, in the
+// standard -Y h +X w orientation.
+fn read_rgbe(bytes: &[u8]) -> io::Result<(usize, usize, Vec<Color<f64>>)> {
+    let mut pos = 0;
+    if !read_line(bytes, &mut pos)?.starts_with("#?") {
+        return Err(invalid("not a Radiance HDR file"));
+    }
+    while !read_line(bytes, &mut pos)?.is_empty() {}
+    let resolution: Vec<&str> = read_line(bytes, &mut pos)?.split_whitespace().collect();
+    if resolution.len() != 4 || resolution[0] != "-Y" || resolution[2] != "+X" {
+        return Err(invalid("unsupported HDR orientation"));
+    }
+    let number = |s: &str| s.parse::<usize>().map_err(|_| invalid("bad HDR resolution"));
+    let (height, width) = (number(resolution[1])?, number(resolution[3])?);
+
+    let next = |pos: &mut usize| -> io::Result<u8> {
+        let b = *bytes.get(*pos).ok_or_else(|| invalid("truncated HDR data"))?;
+        *pos += 1;
+        Ok(b)
+    };
+
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut scanline = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        let rle = (8..0x8000).contains(&width) && bytes.get(pos..pos + 2) == Some(&[2, 2]);
+        if rle {
+            pos += 4;
+            for channel in 0..4 {
+                let mut x = 0;
+                while x < width {
+                    let count = next(&mut pos)? as usize;
+                    if count == 0 {
+                        return Err(invalid("empty HDR run"));
+                    }
+                    if count > 128 {
+                        let value = next(&mut pos)?;
+                        for _ in 0..count - 128 {
+                            scanline.get_mut(x).ok_or_else(|| invalid("HDR run overflows scanline"))?[channel] = value;
+                            x += 1;
+                        }
+                    } else {
+                        for _ in 0..count {
+                            let value = next(&mut pos)?;
+                            scanline.get_mut(x).ok_or_else(|| invalid("HDR run overflows scanline"))?[channel] = value;
+                            x += 1;
+                        }
+                    }
+                }
+            }
+        } else {
+            for rgbe in scanline.iter_mut() {
+                for c in rgbe.iter_mut() {
+                    *c = next(&mut pos)?;
+                }
+            }
+        }
+
+        pixels.extend(scanline.iter().map(|[r, g, b, e]| {
+            if *e == 0 {
+                Color::zero()
+            } else {
+                let scale = 2f64.powi(*e as i32 - 136);
+                Color::new(*r as f64 * scale, *g as f64 * scale, *b as f64 * scale)
+            }
+        }));
+    }
+    Ok((width, height, pixels))
+}
+
+// Portable float map as written by `Film::write_pfm`, rows bottom to top.
+fn read_pfm(bytes: &[u8]) -> io::Result<(usize, usize, Vec<Color<f64>>)> {
+    let mut pos = 0;
+    if read_line(bytes, &mut pos)? != "PF" {
+        return Err(invalid("only RGB float maps are supported"));
+    }
+    let size: Vec<usize> = read_line(bytes, &mut pos)?.split_whitespace().map(|s| s.parse().map_err(|_| invalid("bad PFM size"))).collect::<io::Result<_>>()?;
+    if size.len() != 2 {
+        return Err(invalid("bad PFM size"));
+    }
+    let (width, height) = (size[0], size[1]);
+    let scale: f64 = read_line(bytes, &mut pos)?.parse().map_err(|_| invalid("bad PFM scale"))?;
+
+    let data = bytes.get(pos..pos + width * height * 12).ok_or_else(|| invalid("truncated PFM data"))?;
+    let floats: Vec<f64> = data.chunks_exact(4).map(|b| {
+        let b = [b[0], b[1], b[2], b[3]];
+        (if scale < 0.0 { f32::from_le_bytes(b) } else { f32::from_be_bytes(b) }) as f64
+    }).collect();
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in floats.chunks(width * 3).rev() {
+        pixels.extend(row.chunks_exact(3).map(|c| Color::new(c[0], c[1], c[2])));
+    }
+    Ok((width, height, pixels))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sampling_pdf_matches_lookup() {
+        // A single bright pixel in an otherwise dim map.
+        let (width, height) = (8, 4);
+        let mut pixels = vec![Color::new(0.1, 0.1, 0.1); width * height];
+        pixels[width + 5] = Color::new(100.0, 100.0, 100.0);
+        let env = EnvironmentMap::new(width, height, pixels).with_rotation(30.0);
+
+        let mut bright = 0;
+        for _ in 0..1000 {
+            let (direction, pdf) = env.sample().unwrap();
+            assert!((direction.length() - 1.0).abs() < 1e-9);
+            assert!((pdf - env.pdf(&direction)).abs() < 1e-6 * pdf.max(1.0));
+            if env.radiance(&direction).x > 1.0 {
+                bright += 1;
+            }
+        }
+        assert!(bright > 900);
+
+        // A uniform map samples the sphere uniformly.
+        let uniform = EnvironmentMap::new(64, 32, vec![Color::new(1.0, 1.0, 1.0); 64 * 32]);
+        let pdf = uniform.pdf(&Vec3::new(0.3, 0.2, -0.5));
+        assert!((pdf - 1.0 / (4.0 * PI)).abs() < 0.05 / (4.0 * PI));
+    }
+}
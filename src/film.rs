@@ -0,0 +1,209 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::color::*;
+
+// A named image plane. Pixels are stored row by row, top row first.
+#[derive(Clone)]
+pub struct Channel {
+    pub name: String,
+    pub pixels: Vec<Color<f64>>
+}
+
+// Framebuffer holding the beauty pass and any number of named AOV channels.
+pub struct Film {
+    pub width: usize,
+    pub height: usize,
+    pub channels: Vec<Channel>
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, channels: Vec::new() }
+    }
+
+    pub fn channel(&self, name: &str) -> Option<&Channel> {
+        self.channels.iter().find(|c| c.name == name)
+    }
+
+    pub fn channel_mut(&mut self, name: &str) -> &mut Channel {
+        let index = match self.channels.iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None => {
+                self.channels.push(Channel { name: name.to_string(), pixels: vec![Color::zero(); self.width * self.height] });
+                self.channels.len() - 1
+            }
+        };
+        &mut self.channels[index]
+    }
+
+    // Accumulates a sample into the named channel, creating it on first use.
+    pub fn add(&mut self, name: &str, x: usize, y: usize, value: Color<f64>) {
+        let width = self.width;
+        self.channel_mut(name).pixels[y * width + x] += value;
+    }
+
+    pub fn get(&self, name: &str, x: usize, y: usize) -> Color<f64> {
+        self.channel(name).map_or(Color::zero(), |c| c.pixels[y * self.width + x])
+    }
+
+    // Divide every channel by the number of samples taken per pixel.
+    pub fn resolve(&mut self, samples_per_pixel: i32) {
+        let scale = 1.0 / samples_per_pixel as f64;
+        for channel in self.channels.iter_mut() {
+            for p in channel.pixels.iter_mut() {
+                *p *= scale;
+            }
+        }
+    }
+
+    // Multiplies the named channel, if present, by `factor`.
+    pub fn scale(&mut self, name: &str, factor: f64) {
+        for channel in self.channels.iter_mut().filter(|c| c.name == name) {
+            for p in channel.pixels.iter_mut() {
+                *p *= factor;
+            }
+        }
+    }
+
+    pub fn write_ppm<T: Write>(&self, output: &mut T, name: &str) -> io::Result<()> {
+        let channel = self.channel(name).ok_or_else(|| missing_channel(name))?;
+        output.write_all(format!("P3\n{} {}\n255\n", self.width, self.height).as_bytes())?;
+        for pixel in channel.pixels.iter() {
+            write_color(output, *pixel, 1)?;
+        }
+        Ok(())
+    }
+
+    // Portable float map: one RGB file per channel, rows stored bottom to top.
+    pub fn write_pfm<T: Write>(&self, output: &mut T, name: &str) -> io::Result<()> {
+        let channel = self.channel(name).ok_or_else(|| missing_channel(name))?;
+        output.write_all(format!("PF\n{} {}\n-1.0\n", self.width, self.height).as_bytes())?;
+        for row in channel.pixels.chunks(self.width).rev() {
+            for p in row {
+                for c in p.to_vec() {
+                    output.write_all(&(c as f32).to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Writes `<prefix>.<channel>.pfm` for every channel.
+    pub fn write_pfm_files(&self, prefix: &str) -> io::Result<()> {
+        for channel in self.channels.iter() {
+            let path = format!("{}.{}.pfm", prefix, channel.name);
+            let mut file = BufWriter::new(File::create(path)?);
+            self.write_pfm(&mut file, &channel.name)?;
+        }
+        Ok(())
+    }
+
+    // Uncompressed scanline OpenEXR with every channel as a float layer. The
+    // beauty channel becomes the default R, G, B layer, the others
+    // `<name>.R`, `<name>.G`, `<name>.B`.
+    pub fn write_exr<P: AsRef<Path>>(&self, path: P, beauty: &str) -> io::Result<()> {
+        let mut layers: Vec<(String, &Channel, usize)> = Vec::new();
+        for channel in self.channels.iter() {
+            for (i, component) in ["R", "G", "B"].iter().enumerate() {
+                let name = if channel.name == beauty {
+                    component.to_string()
+                } else {
+                    format!("{}.{}", channel.name, component)
+                };
+                layers.push((name, channel, i));
+            }
+        }
+        // EXR requires channels in alphabetical order.
+        layers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut header = vec![0x76, 0x2f, 0x31, 0x01];
+        header.extend_from_slice(&2i32.to_le_bytes());
+
+        let mut chlist = Vec::new();
+        for (name, _, _) in layers.iter() {
+            chlist.extend_from_slice(name.as_bytes());
+            chlist.push(0);
+            chlist.extend_from_slice(&2i32.to_le_bytes()); // FLOAT
+            chlist.extend_from_slice(&[0, 0, 0, 0]); // pLinear and reserved
+            chlist.extend_from_slice(&1i32.to_le_bytes());
+            chlist.extend_from_slice(&1i32.to_le_bytes());
+        }
+        chlist.push(0);
+        write_attribute(&mut header, "channels", "chlist", &chlist)?;
+        write_attribute(&mut header, "compression", "compression", &[0])?;
+        let mut window = Vec::new();
+        for v in [0, 0, self.width as i32 - 1, self.height as i32 - 1] {
+            window.extend_from_slice(&v.to_le_bytes());
+        }
+        write_attribute(&mut header, "dataWindow", "box2i", &window)?;
+        write_attribute(&mut header, "displayWindow", "box2i", &window)?;
+        write_attribute(&mut header, "lineOrder", "lineOrder", &[0])?;
+        write_attribute(&mut header, "pixelAspectRatio", "float", &1.0f32.to_le_bytes())?;
+        write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8])?;
+        write_attribute(&mut header, "screenWindowWidth", "float", &1.0f32.to_le_bytes())?;
+        header.push(0);
+
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(&header)?;
+
+        // Offset table, then one chunk per scanline.
+        let line_size = layers.len() * self.width * 4;
+        let mut offset = (header.len() + 8 * self.height) as u64;
+        for _ in 0..self.height {
+            out.write_all(&offset.to_le_bytes())?;
+            offset += 8 + line_size as u64;
+        }
+        for y in 0..self.height {
+            out.write_all(&(y as i32).to_le_bytes())?;
+            out.write_all(&(line_size as i32).to_le_bytes())?;
+            for (_, channel, component) in layers.iter() {
+                for x in 0..self.width {
+                    let c = channel.pixels[y * self.width + x].to_vec()[*component];
+                    out.write_all(&(c as f32).to_le_bytes())?;
+                }
+            }
+        }
+        out.flush()
+    }
+}
+
+fn missing_channel(name: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no film channel named {}", name))
+}
+
+fn write_attribute<T: Write>(output: &mut T, name: &str, kind: &str, value: &[u8]) -> io::Result<()> {
+    output.write_all(name.as_bytes())?;
+    output.write_all(&[0])?;
+    output.write_all(kind.as_bytes())?;
+    output.write_all(&[0])?;
+    output.write_all(&(value.len() as i32).to_le_bytes())?;
+    output.write_all(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Film;
+    use crate::color::Color;
+
+    #[test]
+    fn pfm_stores_rows_bottom_up() {
+        let mut film = Film::new(2, 2);
+        film.add("albedo", 0, 0, Color::new(2.0, 2.0, 2.0));
+        film.add("albedo", 1, 1, Color::new(4.0, 0.0, 0.0));
+        film.resolve(2);
+
+        let mut out = Vec::new();
+        film.write_pfm(&mut out, "albedo").unwrap();
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(&out[..header.len()], header);
+
+        let floats: Vec<f32> = out[header.len()..].chunks(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        assert_eq!(floats.len(), 12);
+        // Bottom row first: pixel (1, 1) is the fourth pixel on disk.
+        assert_eq!(floats[3], 2.0);
+        assert_eq!(floats[6], 1.0);
+        assert!(film.write_pfm(&mut Vec::new(), "missing").is_err());
+    }
+}
@@ -1,7 +1,10 @@
+use std::cell::Cell;
 use std::sync::Arc;
 
+use crate::aabb::Aabb;
 use crate::color::Color;
-use crate::vec3::{Vec3, Point3};
+use crate::constants::INFINITY;
+use crate::vec3::{Vec3, Point3, orthonormal_basis};
 use crate::ray::*;
 use crate::material::*;
 
@@ -10,24 +13,176 @@ use crate::material::*;
 pub struct HitRecord<T> {
     pub p: Point3<T>,
     pub normal: Vec3<T>,
+    pub geometric_normal: Vec3<T>,
     pub t: T,
+    pub u: T,
+    pub v: T,
+    // Partial derivatives of the surface point with respect to u and v, or
+    // zero when a shape doesn't provide them.
+    pub dpdu: Vec3<T>,
+    pub dpdv: Vec3<T>,
     pub front_face: bool,
+    pub object_id: usize,
     pub mat_ptr: Arc< dyn Material>
 }
 
-impl<'a> HitRecord<f64>
+impl HitRecord<f64>
 {
-    pub fn new(p: Point3<f64>, normal: Vec3<f64>, t: f64, front_face: bool, mat_ptr: Arc<dyn Material>) -> Self { Self { p, normal, t, front_face , mat_ptr} }
+    pub fn new(p: Point3<f64>, normal: Vec3<f64>, t: f64, front_face: bool, mat_ptr: Arc<dyn Material>) -> Self {
+        Self { p, normal, geometric_normal: normal, t, u: 0.0, v: 0.0, dpdu: Vec3::zero(), dpdv: Vec3::zero(), front_face, object_id: 0, mat_ptr }
+    }
 
     pub fn zero() -> Self {Self::new(Vec3::<f64>::zero(), Vec3::<f64>::zero(), 0.0, false, Arc::new(Lambertian{albedo: Color::new(0.0, 0.0, 0.0)}))}
 
     pub fn set_face_normal(&mut self, r: &Ray<f64>, outward_normal: &Vec3<f64>) {
         self.front_face = r.direction.dot(*outward_normal) < 0.;
         self.normal = if self.front_face {*outward_normal} else {- *outward_normal};
+        self.geometric_normal = self.normal;
+        // Shapes with surface derivatives set them after this.
+        self.dpdu = Vec3::zero();
+        self.dpdv = Vec3::zero();
+    }
+
+    // Orthonormal tangent and bitangent around the shading normal, aligned
+    // with dpdu and dpdv where the shape provides them.
+    pub fn tangent_frame(&self) -> (Vec3<f64>, Vec3<f64>) {
+        let n = self.normal;
+        let tangent = self.dpdu - self.dpdu.dot(n) * n;
+        if tangent.length_squared() < 1e-16 {
+            return orthonormal_basis(n);
+        }
+        let tangent = tangent.unit_vector();
+        let bitangent = n.cross(tangent);
+        if self.dpdv.dot(bitangent) < 0.0 { (tangent, -bitangent) } else { (tangent, bitangent) }
     }
 }
 
-pub trait Hittable
+pub trait Hittable: Send + Sync
 {
     fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool;
+
+    // Returns false for unbounded objects such as infinite planes.
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool;
+
+    // Surface area, zero for objects that can't be sampled as lights.
+    fn area(&self) -> f64 {
+        0.0
+    }
+
+    // Unit direction from `origin` towards a random point on the object, for
+    // sampling emissive objects directly.
+    fn random(&self, _origin: &Point3<f64>) -> Vec3<f64> {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    // Solid angle density with which `random` picks `direction` from
+    // `origin`, zero if the direction misses the object.
+    fn pdf_value(&self, _origin: &Point3<f64>, _direction: &Vec3<f64>) -> f64 {
+        0.0
+    }
+
+    // Appends, in order, every stretch of the ray's whole line that lies
+    // inside the object. Only meaningful for closed objects. The default
+    // walks along the line with `hit`, pairing entering and leaving hits.
+    fn spans(&self, r: Ray<f64>, spans: &mut Vec<Span>) {
+        let mut rec = HitRecord::zero();
+        let mut t_min = -INFINITY;
+        let mut enter: Option<HitRecord<f64>> = None;
+
+        for _ in 0..MAX_SPAN_HITS {
+            if !self.hit(r, t_min, INFINITY, &mut rec) {
+                break;
+            }
+            if rec.front_face {
+                enter = Some(rec.clone());
+            } else {
+                let enter = enter.take().unwrap_or_else(|| Span::unbounded(-INFINITY));
+                spans.push(Span { enter, exit: rec.clone() });
+            }
+            t_min = rec.t + SPAN_EPSILON * (1.0 + rec.t.abs());
+        }
+
+        if let Some(enter) = enter {
+            spans.push(Span { enter, exit: Span::unbounded(INFINITY) });
+        }
+    }
+}
+
+// Solid angle pdf of `direction` from `origin` for a flat or curved surface
+// sampled uniformly by area, found by tracing towards it.
+pub fn area_pdf(object: &dyn Hittable, origin: &Point3<f64>, direction: &Vec3<f64>) -> f64 {
+    let mut rec = HitRecord::zero();
+    let area = object.area();
+    if area <= 0.0 || !object.hit(Ray::new(*origin, *direction), 0.001, INFINITY, &mut rec) {
+        return 0.0;
+    }
+    let distance_squared = rec.t * rec.t * direction.length_squared();
+    let cosine = (direction.dot(rec.normal) / direction.length()).abs();
+    if cosine <= 0.0 {
+        return 0.0;
+    }
+    distance_squared / (cosine * area)
+}
+
+const MAX_SPAN_HITS: usize = 64;
+const SPAN_EPSILON: f64 = 1e-9;
+
+// Part of a ray spent inside a solid, between the hit entering it and the hit
+// leaving it.
+#[derive(Clone)]
+pub struct Span {
+    pub enter: HitRecord<f64>,
+    pub exit: HitRecord<f64>
+}
+
+impl Span {
+    // Placeholder boundary for a span that is open at one end.
+    pub fn unbounded(t: f64) -> HitRecord<f64> {
+        let mut rec = HitRecord::zero();
+        rec.t = t;
+        rec
+    }
+}
+
+thread_local! {
+    static INTERSECTION_TESTS: Cell<u64> = const { Cell::new(0) };
+}
+
+// Counts ray-object intersection tests on the current thread, for the
+// intersection heatmap debug view.
+pub fn record_intersection_test() {
+    INTERSECTION_TESTS.with(|c| c.set(c.get() + 1));
+}
+
+pub fn take_intersection_tests() -> u64 {
+    INTERSECTION_TESTS.with(|c| c.replace(0))
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    // Hits `object` with `r`, then checks that dpdu and dpdv lie in the
+    // surface there and predict where u and v move: stepping a little along
+    // either and hitting the surface again changes only that coordinate.
+    pub fn check_surface_derivatives(object: &dyn Hittable, r: Ray<f64>) {
+        let mut rec = HitRecord::zero();
+        assert!(object.hit(r, 0.001, INFINITY, &mut rec));
+        let n = rec.normal;
+        for (dpd, name) in [(rec.dpdu, "dpdu"), (rec.dpdv, "dpdv")] {
+            assert!(dpd.length() > 0.0, "{} is missing", name);
+            assert!(dpd.dot(n).abs() < 1e-9 * dpd.length(), "{} leaves the surface", name);
+        }
+
+        let h = 1e-5;
+        for (du, dv) in [(h, 0.0), (0.0, h)] {
+            let target = rec.p + du * rec.dpdu + dv * rec.dpdv;
+            let mut moved = HitRecord::zero();
+            assert!(object.hit(Ray::new(target + 1e-3 * n, -n), 0.0, INFINITY, &mut moved));
+            let change_u = (moved.u - rec.u + 0.5).rem_euclid(1.0) - 0.5;
+            let change_v = moved.v - rec.v;
+            assert!((change_u - du).abs() < 1e-3 * h && (change_v - dv).abs() < 1e-3 * h,
+                    "stepping ({}, {}) moved uv by ({}, {})", du, dv, change_u, change_v);
+        }
+    }
 }
@@ -1,12 +1,22 @@
 use std::sync::Arc;
 
+use crate::aabb::*;
 use crate::hittable::*;
 
+const MAX_CUTOUT_HITS: usize = 64;
+const CUTOUT_EPSILON: f64 = 1e-9;
+
 pub struct HittableList<'a>
 {
     pub objects: Vec<Arc<dyn 'a + Hittable>>
 }
 
+impl<'a> Default for HittableList<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> HittableList<'a>
 {
     pub fn new() -> HittableList<'a> {
@@ -23,20 +33,88 @@ impl<'a> HittableList<'a>
     pub fn clear(&mut self) {
         self.objects.clear();
     }
+}
 
-    pub fn hit(&self, r: crate::ray::Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+impl<'a> Hittable for HittableList<'a>
+{
+    fn hit(&self, r: crate::ray::Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
         let mut temp_rec = HitRecord::zero();
         let mut hit_anything = false;
         let mut closest_so_far = t_max;
 
-        for object in self.objects.iter() {
-            if object.as_ref().hit(r, t_min, closest_so_far, &mut temp_rec) {
+        for (i, object) in self.objects.iter().enumerate() {
+            // Hits on cut out parts of a surface are skipped by searching
+            // again past them, so they cost no bounce and also let shadow
+            // rays through.
+            let mut t_near = t_min;
+            for _ in 0..MAX_CUTOUT_HITS {
+                record_intersection_test();
+                if !object.as_ref().hit(r, t_near, closest_so_far, &mut temp_rec) {
+                    break;
+                }
+                if temp_rec.mat_ptr.is_transparent(&r, &temp_rec) {
+                    t_near = temp_rec.t + CUTOUT_EPSILON * (1.0 + temp_rec.t.abs());
+                    continue;
+                }
                 hit_anything = true;
                 closest_so_far = temp_rec.t;
+                temp_rec.object_id = i;
                 *rec = temp_rec.clone();
-            };
+                break;
+            }
         };
 
         hit_anything
     }
-}
\ No newline at end of file
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        if self.objects.is_empty() {
+            return false;
+        }
+
+        let mut temp_box = Aabb::zero();
+        let mut first_box = true;
+
+        for object in self.objects.iter() {
+            if !object.bounding_box(time0, time1, &mut temp_box) {
+                return false;
+            }
+            *output_box = if first_box { temp_box } else { surrounding_box(output_box, &temp_box) };
+            first_box = false;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::*;
+    use crate::quad::Quad;
+    use crate::ray::Ray;
+    use crate::texture::ImageTexture;
+    use crate::vec3::*;
+
+    #[test]
+    fn cutout_hits_are_skipped() {
+        // The upper quad's v runs along x; its half with x < 0 is cut away.
+        let gray = Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)));
+        let mask = Arc::new(ImageTexture::new(1, 2, vec![Color::new(1.0, 1.0, 1.0), Color::zero()]));
+        let leaf = Arc::new(Cutout::new(gray.clone(), mask, AlphaMode::Threshold(0.5)));
+
+        let mut world = HittableList::new();
+        world.add(Quad::xz(-1.0, 1.0, -1.0, 1.0, 1.0, leaf));
+        world.add(Quad::xz(-1.0, 1.0, -1.0, 1.0, 0.0, gray));
+
+        let mut rec = HitRecord::zero();
+        let through = Ray::new(Point3::new(-0.5, 2.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(world.hit(through, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.0).abs() < 1e-9 && rec.object_id == 1);
+
+        let blocked = Ray::new(Point3::new(0.5, 2.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(world.hit(blocked, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 1.0).abs() < 1e-9 && rec.object_id == 0);
+    }
+}
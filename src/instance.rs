@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::hittable::*;
+use crate::motion::*;
+use crate::ray::Ray;
+use crate::transform::*;
+
+// Places a shared object in the world through an object-to-world transform,
+// which may change over the shutter interval. Many instances can point at the
+// same object without copying it.
+#[derive(Clone)]
+pub struct Instance {
+    pub object: Arc<dyn Hittable>,
+    pub transform: AnimatedTransform
+}
+
+impl Instance {
+    pub fn new(object: Arc<dyn Hittable>, transform: Transform) -> Self {
+        Self { object, transform: AnimatedTransform::Static(transform) }
+    }
+
+    pub fn animated(object: Arc<dyn Hittable>, keyframes: Vec<Keyframe>) -> Self {
+        Self { object, transform: AnimatedTransform::keyframed(keyframes) }
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let transform = self.transform.at(r.time);
+        let object_ray = transform.inverse().ray(&r);
+        if !self.object.hit(object_ray, t_min, t_max, rec) {
+            return false;
+        }
+
+        // front_face stays valid: n.d is unchanged when n transforms by the
+        // inverse transpose and d by the matrix itself.
+        rec.p = transform.point(rec.p);
+        rec.normal = transform.normal(rec.normal).unit_vector();
+        rec.geometric_normal = transform.normal(rec.geometric_normal).unit_vector();
+        rec.dpdu = transform.vector(rec.dpdu);
+        rec.dpdv = transform.vector(rec.dpdv);
+
+        true
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        let mut object_box = Aabb::zero();
+        if !self.object.bounding_box(time0, time1, &mut object_box) {
+            return false;
+        }
+        *output_box = self.transform.bounding_box(&object_box, time0, time1);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::quad::Quad;
+    use crate::vec3::*;
+
+    #[test]
+    fn hits_carry_the_transform() {
+        // A unit quad in the xy plane, stretched along x, turned to face +x
+        // and pushed back along z.
+        let quad = Arc::new(Quad::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)))));
+        let transform = Transform::translate(Vec3::new(0.0, 0.0, -5.0)) * Transform::rotate_y(90.0) * Transform::scale(Vec3::new(2.0, 1.0, 1.0));
+        let instance = Instance::new(quad, transform);
+
+        let mut rec = HitRecord::zero();
+        let r = Ray::new(Point3::new(5.0, 0.5, -6.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(instance.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 5.0).abs() < 1e-9);
+        assert!((rec.p - Point3::new(0.0, 0.5, -6.0)).length() < 1e-9);
+        assert!((rec.normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((rec.geometric_normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!(rec.front_face);
+        assert!((rec.u - 0.5).abs() < 1e-9 && (rec.v - 0.5).abs() < 1e-9);
+
+        // Tangents follow the scale and rotation but not the translation.
+        assert!((rec.dpdu - Vec3::new(0.0, 0.0, -2.0)).length() < 1e-9);
+        assert!((rec.dpdv - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,538 @@
+use std::str::FromStr;
+
+use crate::color::*;
+use crate::constants::INFINITY;
+use crate::hittable::*;
+use crate::hittable_list::*;
+use crate::material::Lobe;
+use crate::ray::*;
+use crate::scene::*;
+use crate::spectrum::*;
+use crate::utilities::rand_double;
+use crate::vec3::*;
+
+pub fn ray_color(r: Ray<f64>, scene: &Scene, depth: i32) -> Color<f64>
+{
+    trace(r, scene, depth, None)
+}
+
+// Auxiliary values captured at the first hit along a camera ray, along with
+// the beauty radiance split by the lobe of that first bounce.
+#[derive(Debug, Clone, Copy)]
+pub struct Aovs {
+    pub albedo: Color<f64>,
+    pub normal: Vec3<f64>,
+    pub depth: f64,
+    pub position: Point3<f64>,
+    pub object_id: f64,
+    pub diffuse_direct: Color<f64>,
+    pub diffuse_indirect: Color<f64>,
+    pub specular_direct: Color<f64>,
+    pub specular_indirect: Color<f64>,
+}
+
+impl Aovs {
+    pub fn zero() -> Self {
+        Self {
+            albedo: Color::zero(),
+            normal: Vec3::zero(),
+            depth: 0.0,
+            position: Point3::zero(),
+            object_id: 0.0,
+            diffuse_direct: Color::zero(),
+            diffuse_indirect: Color::zero(),
+            specular_direct: Color::zero(),
+            specular_indirect: Color::zero(),
+        }
+    }
+
+    // Film channel names and values, scalars splatted to all three components.
+    pub fn channels(&self) -> [(&'static str, Color<f64>); 9] {
+        [
+            ("albedo", self.albedo),
+            ("normal", self.normal),
+            ("depth", Color::new(self.depth, self.depth, self.depth)),
+            ("position", self.position),
+            ("object_id", Color::new(self.object_id, self.object_id, self.object_id)),
+            ("diffuse_direct", self.diffuse_direct),
+            ("diffuse_indirect", self.diffuse_indirect),
+            ("specular_direct", self.specular_direct),
+            ("specular_indirect", self.specular_indirect),
+        ]
+    }
+
+    // Light seen directly by the camera belongs to no pass; light arriving
+    // after one bounce is direct, after more is indirect.
+    fn add_light(&mut self, first_specular: bool, bounce: i32, contribution: Color<f64>) {
+        let pass = match (first_specular, bounce) {
+            (_, 0) => return,
+            (false, 1) => &mut self.diffuse_direct,
+            (false, _) => &mut self.diffuse_indirect,
+            (true, 1) => &mut self.specular_direct,
+            (true, _) => &mut self.specular_indirect,
+        };
+        *pass += contribution;
+    }
+}
+
+pub fn ray_color_aovs(r: Ray<f64>, scene: &Scene, depth: i32) -> (Color<f64>, Aovs) {
+    let mut aovs = Aovs::zero();
+    let color = trace(r, scene, depth, Some(&mut aovs));
+    (color, aovs)
+}
+
+// Paths whose camera ray carries a wavelength are traced spectrally: every
+// RGB quantity is upsampled to its value at that wavelength, splatted across
+// the three channels, and the result is converted back to RGB at the end.
+//
+// At hits on materials that report a scattering pdf, the environment and a
+// light chosen by the scene's light sampler are also sampled directly through
+// shadow rays, and the strategies are combined with multiple importance
+// sampling.
+fn trace(r: Ray<f64>, scene: &Scene, depth: i32, mut aovs: Option<&mut Aovs>) -> Color<f64> {
+    let world = &scene.world;
+    let environment = scene.environment.as_ref();
+    let lambda = r.wavelength;
+
+    let mut rec = HitRecord::zero();
+    let mut ray = r;
+    let mut color = Color::zero();
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    let mut first_specular = false;
+    // Pdf of the last scattered direction, when light sampling could also
+    // have produced it.
+    let mut scatter_pdf: Option<f64> = None;
+    let mut scatter_origin = ray.origin;
+
+    // Once we've exceeded the ray bounce limit, no more light is gathered.
+    for bounce in 0..depth {
+        if !world.hit(ray, 0.001, INFINITY, &mut rec) {
+            let weight = scatter_pdf.map_or(1.0, |pdf| power_heuristic(pdf, environment.pdf(&ray.direction)));
+            let contribution = weight * throughput * to_path(environment.radiance(&ray.direction), lambda);
+            color += contribution;
+            if let Some(aovs) = aovs.as_deref_mut() {
+                aovs.add_light(first_specular, bounce, to_rgb(contribution, lambda));
+            }
+            break;
+        }
+
+        let mut emitted = rec.mat_ptr.emitted(rec.u, rec.v, &rec.p);
+        if let (Some(pdf), Some(light)) = (scatter_pdf, scene.emitter(rec.object_id)) {
+            if emitted.length_squared() > 0.0 {
+                let light_pdf = scene.light_sampler().pmf(&scatter_origin, light) * scene.lights[light].pdf(&scatter_origin, &ray.direction);
+                emitted = power_heuristic(pdf, light_pdf) * emitted;
+            }
+        }
+        let emitted = throughput * to_path(emitted, lambda);
+        color += emitted;
+        if let Some(aovs) = aovs.as_deref_mut() {
+            aovs.add_light(first_specular, bounce, to_rgb(emitted, lambda));
+        }
+
+        let mut scattered = Ray::x_unit();
+        let mut attenuation = Vec3::new(1.0, 1.0, 1.0);
+        let lobe = rec.mat_ptr.as_ref().scatter_lobe(&ray, &rec, &mut attenuation, &mut scattered);
+
+        if bounce == 0 {
+            first_specular = lobe == Some(Lobe::Specular);
+            if let Some(aovs) = aovs.as_deref_mut() {
+                aovs.albedo = if lobe.is_some() { attenuation } else { Color::zero() };
+                aovs.normal = rec.normal;
+                aovs.depth = rec.t * ray.direction.length();
+                aovs.position = rec.p;
+                aovs.object_id = rec.object_id as f64;
+            }
+        }
+
+        let Some(lobe) = lobe else { break; };
+
+        // Lights are sampled even when a specular lobe was picked, as the
+        // material may also have diffuse ones.
+        if !rec.mat_ptr.is_specular() {
+            let contribution = throughput * direct_light(scene, &ray, &rec);
+            color += contribution;
+            if let Some(aovs) = aovs.as_deref_mut() {
+                aovs.add_light(first_specular, bounce + 1, to_rgb(contribution, lambda));
+            }
+        }
+
+        // Light sampling can't find specular directions, so emitters seen
+        // along them keep their full weight.
+        let pdf = match lobe {
+            Lobe::Diffuse => rec.mat_ptr.scattering_pdf(&ray, &rec, &scattered),
+            Lobe::Specular => 0.0,
+        };
+        scatter_pdf = if pdf > 0.0 { Some(pdf) } else { None };
+
+        throughput = throughput * to_path(attenuation, lambda);
+        scatter_origin = rec.p;
+        ray = scattered;
+        ray.wavelength = lambda;
+    }
+
+    to_rgb(color, lambda)
+}
+
+// Keeps shadow rays from reaching the light they were aimed at.
+const SHADOW_EPSILON: f64 = 0.001;
+
+// Light scattered at `rec` towards the incoming ray straight from the
+// environment and from one light, one shadow ray each, through the
+// material's diffuse lobes. Returned as a path quantity, before the path
+// throughput.
+fn direct_light(scene: &Scene, ray: &Ray<f64>, rec: &HitRecord<f64>) -> Color<f64> {
+    let lambda = ray.wavelength;
+    let bsdf = |direction: Vec3<f64>| {
+        let scattered = Ray::with_time(rec.p, direction, ray.time);
+        (rec.mat_ptr.scattering_pdf(ray, rec, &scattered), to_path(rec.mat_ptr.eval(ray, rec, &scattered), lambda))
+    };
+    let unoccluded = |direction: Vec3<f64>, distance: f64| {
+        let shadow = Ray::with_time(rec.p, direction, ray.time);
+        !scene.world.hit(shadow, 0.001, distance - SHADOW_EPSILON, &mut HitRecord::zero())
+    };
+
+    let mut total = Color::zero();
+    if let Some((direction, light_pdf)) = scene.environment.sample() {
+        let (material_pdf, f) = bsdf(direction);
+        if material_pdf > 0.0 && unoccluded(direction, INFINITY) {
+            let weight = power_heuristic(light_pdf, material_pdf);
+            let radiance = to_path(scene.environment.radiance(&direction), lambda);
+            total += (weight / light_pdf) * f * radiance;
+        }
+    }
+
+    let chosen = scene.light_sampler().sample(&rec.p, rand_double());
+    if let Some((sample, pmf)) = chosen.and_then(|(light, pmf)| Some((scene.lights[light].sample(&rec.p, ray.time)?, pmf))) {
+        let light_pdf = sample.pdf * pmf;
+        let (material_pdf, f) = bsdf(sample.direction);
+        if material_pdf > 0.0 && light_pdf > 0.0 && unoccluded(sample.direction, sample.distance) {
+            let weight = if sample.delta { 1.0 } else { power_heuristic(light_pdf, material_pdf) };
+            let radiance = to_path(sample.radiance, lambda);
+            total += (weight / light_pdf) * f * radiance;
+        }
+    }
+    total
+}
+
+// An RGB quantity as carried by a path: unchanged on RGB paths, and its
+// value at the path's wavelength, in all three channels, on spectral ones.
+fn to_path(c: Color<f64>, lambda: f64) -> Color<f64> {
+    if lambda > 0.0 {
+        let s = rgb_to_spectrum(c, lambda);
+        Color::new(s, s, s)
+    } else {
+        c
+    }
+}
+
+fn to_rgb(c: Color<f64>, lambda: f64) -> Color<f64> {
+    if lambda > 0.0 { spectral_to_rgb(c.x, lambda) } else { c }
+}
+
+// Weight for a sample from a strategy with density `pdf`, when another
+// strategy could have produced it with density `other_pdf`.
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b > 0.0 { a / (a + b) } else { 0.0 }
+}
+
+// False-color views of what the first hit along a camera ray recorded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugMode {
+    ShadingNormal,
+    GeometricNormal,
+    // Hit distance, mapped onto a heatmap over [0, max_t].
+    Depth { max_t: f64 },
+    FrontFace,
+    Uv,
+    ObjectId,
+    MaterialType,
+    // Number of ray-object tests, mapped onto a heatmap over [0, max_tests].
+    IntersectionTests { max_tests: f64 },
+}
+
+impl FromStr for DebugMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(DebugMode::ShadingNormal),
+            "geometric-normal" => Ok(DebugMode::GeometricNormal),
+            "depth" => Ok(DebugMode::Depth { max_t: 20.0 }),
+            "front-face" => Ok(DebugMode::FrontFace),
+            "uv" => Ok(DebugMode::Uv),
+            "object" => Ok(DebugMode::ObjectId),
+            "material" => Ok(DebugMode::MaterialType),
+            "heatmap" => Ok(DebugMode::IntersectionTests { max_tests: 32.0 }),
+            _ => Err(format!("unknown debug mode: {}", s)),
+        }
+    }
+}
+
+pub fn debug_color(r: Ray<f64>, world: &HittableList, mode: DebugMode) -> Color<f64> {
+    let mut rec = HitRecord::zero();
+
+    take_intersection_tests();
+    let hit = world.hit(r, 0.001, INFINITY, &mut rec);
+    let tests = take_intersection_tests();
+
+    match mode {
+        DebugMode::IntersectionTests { max_tests } => heatmap(tests as f64 / max_tests),
+        _ if !hit => Color::zero(),
+        DebugMode::ShadingNormal => 0.5 * (rec.normal + 1.0),
+        DebugMode::GeometricNormal => 0.5 * (rec.geometric_normal + 1.0),
+        DebugMode::Depth { max_t } => heatmap(rec.t * r.direction.length() / max_t),
+        DebugMode::FrontFace => {
+            if rec.front_face { Color::new(0.0, 1.0, 0.0) } else { Color::new(1.0, 0.0, 0.0) }
+        }
+        DebugMode::Uv => Color::new(rec.u, rec.v, 0.0),
+        DebugMode::ObjectId => hash_color(rec.object_id as u64),
+        DebugMode::MaterialType => {
+            hash_color(rec.mat_ptr.name().bytes().fold(0u64, |h, b| h.wrapping_mul(31).wrapping_add(b as u64)))
+        }
+    }
+}
+
+// Blue through green to red as x goes from 0 to 1.
+pub fn heatmap(x: f64) -> Color<f64> {
+    let x = x.clamp(0.0, 1.0);
+    if x < 0.5 {
+        let s = x * 2.0;
+        Color::new(0.0, s, 1.0 - s)
+    } else {
+        let s = (x - 0.5) * 2.0;
+        Color::new(s, 1.0 - s, 0.0)
+    }
+}
+
+// A stable, well spread color for an integer key.
+fn hash_color(key: u64) -> Color<f64> {
+    let mut h = key.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^= h >> 31;
+    Color::new(
+        (h & 0xff) as f64 / 255.0,
+        ((h >> 8) & 0xff) as f64 / 255.0,
+        ((h >> 16) & 0xff) as f64 / 255.0,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::environment::{EnvironmentMap, GradientSky};
+    use crate::constants::PI;
+    use crate::light::PointLight;
+    use crate::light_sampler::LightSampling;
+    use crate::material::{Coated, DiffuseLight, Lambertian, Material, Metal, Mix, MixWeight, NormalMapped, Perturbation};
+    use crate::sphere::Sphere;
+    use crate::texture::SolidColor;
+
+    #[test]
+    fn furnace_with_environment_sampling() {
+        // A convex diffuse object under uniform light reflects exactly its
+        // albedo, whichever strategy finds the light.
+        let mut world = HittableList::new();
+        world.add(Sphere::new(Point3::zero(), 1.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)))));
+        let uniform = EnvironmentMap::new(16, 8, vec![Color::new(1.0, 1.0, 1.0); 16 * 8]);
+        let scene = Scene::new(world).with_environment(Arc::new(uniform));
+
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let n = 20_000;
+        let mut total = Color::zero();
+        for _ in 0..n {
+            total += ray_color(r, &scene, 10);
+        }
+        let mean = total / n as f64;
+        assert!((mean.y - 0.5).abs() < 0.02, "got {}", mean);
+    }
+
+    #[test]
+    fn point_light_is_found_by_shadow_rays() {
+        // Diffuse ground directly below a point light, in a black sky, seen
+        // after a single bounce.
+        let mut world = HittableList::new();
+        world.add(Sphere::new(Point3::new(0.0, -1.0, 0.0), 1.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)))));
+        let black = GradientSky { horizon: Color::zero(), zenith: Color::zero() };
+        let mut scene = Scene::new(world).with_environment(Arc::new(black));
+        scene.add_light(PointLight::new(Point3::new(0.0, 1.0, 0.0), Color::new(4.0, 4.0, 4.0)));
+
+        let r = Ray::new(Point3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+        let color = ray_color(r, &scene, 1);
+        assert!((color.y - 2.0 / PI).abs() < 1e-9, "got {}", color);
+
+        // The light itself is invisible to camera rays.
+        let through_light = Ray::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(ray_color(through_light, &scene, 1).length(), 0.0);
+    }
+
+    #[test]
+    fn point_light_reaches_wrapped_materials() {
+        // As above, with the ground's Lambertian wrapped in other materials,
+        // which must light only their diffuse parts.
+        let base = |albedo: f64| -> Arc<dyn Material> { Arc::new(Lambertian::new(&Color::new(albedo, albedo, albedo))) };
+        let mirror: Arc<dyn Material> = Arc::new(Metal::new(&Color::new(1.0, 1.0, 1.0), 0.0));
+        let r = Ray::new(Point3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+        let render = |material: Arc<dyn Material>| {
+            let mut world = HittableList::new();
+            world.add(Sphere::new(Point3::new(0.0, -1.0, 0.0), 1.0, material));
+            let black = GradientSky { horizon: Color::zero(), zenith: Color::zero() };
+            let mut scene = Scene::new(world).with_environment(Arc::new(black));
+            scene.add_light(PointLight::new(Point3::new(0.0, 1.0, 0.0), Color::new(4.0, 4.0, 4.0)));
+            ray_color(r, &scene, 1).y
+        };
+
+        // A flat normal map changes nothing.
+        let flat = Perturbation::NormalMap(Arc::new(SolidColor::new(&Color::new(0.5, 0.5, 1.0))));
+        let color = render(Arc::new(NormalMapped::new(base(0.5), flat)));
+        assert!((color - 2.0 / PI).abs() < 1e-9, "got {}", color);
+
+        // Half diffuse, half mirror: only the diffuse half sees the light,
+        // whichever half the path continues through.
+        let color = render(Arc::new(Mix::new(base(0.02), mirror.clone(), MixWeight::Constant(0.5))));
+        assert!((color - 0.5 * 0.02 * 4.0 / PI).abs() < 1e-9, "got {}", color);
+
+        // Under a clear coat the light crosses the coating twice: in straight
+        // down, and out towards the viewer at 45 degrees.
+        let schlick = |cos: f64| 0.04 + 0.96 * (1.0 - cos).powi(5);
+        let expected = 0.02 * 4.0 / PI * (1.0 - schlick(1.0)) * (1.0 - schlick(0.5f64.sqrt()));
+        let color = render(Arc::new(Coated::new(base(0.02), 1.5, 0.0)));
+        assert!((color - expected).abs() < 1e-9, "got {} expected {}", color, expected);
+    }
+
+    #[test]
+    fn mirror_lobes_see_emitters_at_full_weight() {
+        // An emitter seen in the mirror half of a mix can only be found by
+        // scattering, so it must not be down-weighted for light sampling.
+        let dark = Arc::new(Lambertian::new(&Color::zero()));
+        let mirror = Arc::new(Metal::new(&Color::new(1.0, 1.0, 1.0), 0.0));
+        let mut world = HittableList::new();
+        world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, Arc::new(Mix::new(dark, mirror, MixWeight::Constant(0.5)))));
+        let black = GradientSky { horizon: Color::zero(), zenith: Color::zero() };
+        let mut scene = Scene::new(world).with_environment(Arc::new(black));
+        scene.add_emitter(Sphere::new(Point3::new(-2.0, 2.0, 0.0), 0.5, Arc::new(DiffuseLight::new(&Color::new(4.0, 4.0, 4.0)))));
+
+        // Each path either reflects into the emitter or is absorbed.
+        let r = Ray::new(Point3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+        let colors: Vec<f64> = (0..200).map(|_| ray_color(r, &scene, 2).y).collect();
+        assert!(colors.iter().all(|c| *c == 0.0 || (c - 4.0).abs() < 1e-9), "got {:?}", colors);
+        assert!(colors.iter().any(|c| *c > 0.0));
+    }
+
+    #[test]
+    fn emitters_match_analytic_irradiance() {
+        // A small emissive sphere above diffuse ground lights it with
+        // irradiance pi * Le * (r / d)^2, whichever light sampler is used.
+        let gray = Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)));
+        let emit = Arc::new(DiffuseLight::new(&Color::new(4.0, 4.0, 4.0)));
+        let black = GradientSky { horizon: Color::zero(), zenith: Color::zero() };
+        let expected = 0.5 * 4.0 * (0.5f64 / 2.0).powi(2);
+
+        for sampling in [LightSampling::Uniform, LightSampling::Power, LightSampling::Bvh] {
+            let mut world = HittableList::new();
+            world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, gray.clone()));
+            let mut scene = Scene::new(world).with_environment(Arc::new(black)).with_light_sampling(sampling);
+            scene.add_emitter(Sphere::new(Point3::new(0.0, 2.0, 0.0), 0.5, emit.clone()));
+            scene.add_light(PointLight::new(Point3::new(50.0, 2.0, 0.0), Color::new(1e-3, 1e-3, 1e-3)));
+
+            let r = Ray::new(Point3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+            let n = 20_000;
+            let mut total = Color::zero();
+            for _ in 0..n {
+                total += ray_color(r, &scene, 2);
+            }
+            let mean = total / n as f64;
+            assert!((mean.y - expected).abs() < 0.04 * expected, "{:?} got {}", sampling, mean);
+        }
+    }
+
+    // Two unit spheres three apart in front of the origin, the first
+    // Lambertian and the second metal, and a ray hitting the first head on at
+    // distance 2.
+    fn debug_scene() -> (HittableList<'static>, Ray<f64>) {
+        let mut world = HittableList::new();
+        world.add(Sphere::new(Point3::new(0.0, 0.0, -3.0), 1.0, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)))));
+        world.add(Sphere::new(Point3::new(3.0, 0.0, -3.0), 1.0, Arc::new(Metal::new(&Color::new(0.5, 0.5, 0.5), 0.0))));
+        (world, Ray::new(Point3::zero(), Vec3::new(0.0, 0.0, -1.0)))
+    }
+
+    fn close(a: Color<f64>, b: Color<f64>) -> bool {
+        (a - b).length() < 1e-9
+    }
+
+    #[test]
+    fn debug_shading_normal() {
+        let (world, r) = debug_scene();
+        assert!(close(debug_color(r, &world, DebugMode::ShadingNormal), Color::new(0.5, 0.5, 1.0)));
+        let miss = Ray::new(Point3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        assert!(close(debug_color(miss, &world, DebugMode::ShadingNormal), Color::zero()));
+    }
+
+    #[test]
+    fn debug_geometric_normal() {
+        let (world, r) = debug_scene();
+        assert!(close(debug_color(r, &world, DebugMode::GeometricNormal), Color::new(0.5, 0.5, 1.0)));
+    }
+
+    #[test]
+    fn debug_depth() {
+        let (world, r) = debug_scene();
+        assert!(close(debug_color(r, &world, DebugMode::Depth { max_t: 4.0 }), heatmap(0.5)));
+        assert!(close(heatmap(0.5), Color::new(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn debug_front_face() {
+        let (world, r) = debug_scene();
+        assert!(close(debug_color(r, &world, DebugMode::FrontFace), Color::new(0.0, 1.0, 0.0)));
+        let inside = Ray::new(Point3::new(0.0, 0.0, -3.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(close(debug_color(inside, &world, DebugMode::FrontFace), Color::new(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn debug_uv() {
+        let (world, r) = debug_scene();
+        assert!(close(debug_color(r, &world, DebugMode::Uv), Color::new(0.25, 0.5, 0.0)));
+    }
+
+    #[test]
+    fn debug_object_id() {
+        let (world, r) = debug_scene();
+        let second = Ray::new(Point3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let first_color = debug_color(r, &world, DebugMode::ObjectId);
+        assert!(close(first_color, hash_color(0)));
+        assert!(close(debug_color(second, &world, DebugMode::ObjectId), hash_color(1)));
+        assert!(!close(first_color, hash_color(1)));
+    }
+
+    #[test]
+    fn debug_material_type() {
+        let (mut world, r) = debug_scene();
+        world.add(Sphere::new(Point3::new(-3.0, 0.0, -3.0), 1.0, Arc::new(Lambertian::new(&Color::new(0.9, 0.1, 0.1)))));
+        let metal = Ray::new(Point3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let other_lambertian = Ray::new(Point3::new(-3.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let lambertian = debug_color(r, &world, DebugMode::MaterialType);
+        assert!(close(lambertian, debug_color(other_lambertian, &world, DebugMode::MaterialType)));
+        assert!(!close(lambertian, debug_color(metal, &world, DebugMode::MaterialType)));
+    }
+
+    #[test]
+    fn debug_intersection_tests() {
+        // One test per object in the list, whether or not anything is hit.
+        let (world, r) = debug_scene();
+        let mode = DebugMode::IntersectionTests { max_tests: 4.0 };
+        assert!(close(debug_color(r, &world, mode), heatmap(0.5)));
+        let miss = Ray::new(Point3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        assert!(close(debug_color(miss, &world, mode), heatmap(0.5)));
+
+        // The counter is per thread and reset when taken.
+        take_intersection_tests();
+        record_intersection_test();
+        record_intersection_test();
+        assert_eq!(take_intersection_tests(), 2);
+        assert_eq!(take_intersection_tests(), 0);
+    }
+}
@@ -3,13 +3,44 @@ pub mod color;
 pub mod ray;
 pub mod hittable;
 pub mod sphere;
+pub mod moving_sphere;
+pub mod plane;
+pub mod disk;
+pub mod quad;
+pub mod cuboid;
+pub mod cylinder;
+pub mod cone;
+pub mod torus;
+pub mod capsule;
+pub mod poly;
+pub mod aabb;
+pub mod transform;
+pub mod instance;
+pub mod motion;
+pub mod csg;
+pub mod constant_medium;
+pub mod volume;
+pub mod perlin;
+pub mod spectrum;
+pub mod thin_film;
+pub mod texture;
 pub mod hittable_list;
+pub mod distribution;
+pub mod environment;
+pub mod sky;
+pub mod light;
+pub mod light_sampler;
+pub mod scene;
 pub mod camera;
+pub mod stereo;
 pub mod material;
+pub mod integrator;
+pub mod film;
+pub mod denoise;
 
 pub mod constants {
-    pub const INFINITY: f64 = std::f64::INFINITY;
-    pub const PI: f64 = 3.1415926535897932385;
+    pub const INFINITY: f64 = f64::INFINITY;
+    pub const PI: f64 = std::f64::consts::PI;
 }
 
 pub mod utilities {
@@ -25,8 +56,7 @@ pub mod utilities {
 
     pub fn rand_double() -> f64 {
         let mut rng = rand::thread_rng();
-        let r = rng.gen();
-        r
+        rng.gen()
     }
 
     pub fn random_double(min: f64, max: f64) -> f64 {
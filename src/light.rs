@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::color::*;
+use crate::constants::{INFINITY, PI};
+use crate::environment::luminance;
+use crate::hittable::*;
+use crate::ray::Ray;
+use crate::utilities::degrees_to_radians;
+use crate::vec3::*;
+
+// Light arriving at a point from one sampled direction.
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    // Unit direction from the shaded point towards the light.
+    pub direction: Vec3<f64>,
+    // Distance to the light along `direction`, infinite for distant lights.
+    pub distance: f64,
+    // Incident radiance, or irradiance for delta lights.
+    pub radiance: Color<f64>,
+    // Solid angle density of `direction`, or one for delta lights.
+    pub pdf: f64,
+    // Delta lights can only be reached by sampling them.
+    pub delta: bool
+}
+
+// Lights sampled with shadow rays. Delta lights are not part of the world
+// geometry, so they never appear to the camera; area lights wrap an emissive
+// object that is also in the world.
+pub trait Light: Send + Sync {
+    // Light reaching `p` at `time`, the moment of the ray being shaded.
+    fn sample(&self, p: &Point3<f64>, time: f64) -> Option<LightSample>;
+
+    // Solid angle density with which `sample` picks `direction` from `p`,
+    // zero for delta lights.
+    fn pdf(&self, _p: &Point3<f64>, _direction: &Vec3<f64>) -> f64 {
+        0.0
+    }
+
+    // Luminance of the total emitted power, used to choose between lights.
+    fn power(&self) -> f64;
+
+    // Region the light is emitted from, None for lights infinitely far away.
+    fn bounds(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+// Radiates `intensity` (per steradian) equally in every direction.
+#[derive(Debug, Clone, Copy)]
+pub struct PointLight {
+    pub position: Point3<f64>,
+    pub intensity: Color<f64>
+}
+
+impl PointLight {
+    pub fn new(position: Point3<f64>, intensity: Color<f64>) -> Self {
+        Self { position, intensity }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, p: &Point3<f64>, _time: f64) -> Option<LightSample> {
+        let to_light = self.position - *p;
+        let distance = to_light.length();
+        if distance <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction: to_light / distance,
+            distance,
+            radiance: self.intensity / (distance * distance),
+            pdf: 1.0,
+            delta: true,
+        })
+    }
+
+    fn power(&self) -> f64 {
+        4.0 * PI * luminance(&self.intensity)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.position, self.position))
+    }
+}
+
+// Point light restricted to a cone around `direction`. Full intensity within
+// `falloff_start` degrees of the axis, fading smoothly to nothing at
+// `cone_angle` degrees. An optional profile, like the vertical angles of an
+// IES file, scales the intensity by angle from the axis.
+#[derive(Debug, Clone)]
+pub struct SpotLight {
+    pub position: Point3<f64>,
+    pub direction: Vec3<f64>,
+    pub intensity: Color<f64>,
+    pub cone_angle: f64,
+    pub falloff_start: f64,
+    // Multipliers at evenly spaced angles from 0 to `cone_angle` degrees,
+    // linearly interpolated.
+    pub profile: Option<Vec<f64>>
+}
+
+impl SpotLight {
+    pub fn new(position: Point3<f64>, target: Point3<f64>, intensity: Color<f64>, cone_angle: f64, falloff_start: f64) -> Self {
+        Self { position, direction: (target - position).unit_vector(), intensity, cone_angle, falloff_start, profile: None }
+    }
+
+    pub fn with_profile(mut self, profile: Vec<f64>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    // Fraction of the full intensity leaving along unit direction `w`.
+    pub fn falloff(&self, w: &Vec3<f64>) -> f64 {
+        let cos_theta = w.dot(self.direction);
+        let cos_outer = degrees_to_radians(self.cone_angle).cos();
+        let cos_inner = degrees_to_radians(self.falloff_start.min(self.cone_angle)).cos();
+        if cos_theta <= cos_outer {
+            return 0.0;
+        }
+
+        let smooth = if cos_theta >= cos_inner || cos_inner <= cos_outer {
+            1.0
+        } else {
+            let t = (cos_theta - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        };
+
+        let shaped = match &self.profile {
+            Some(profile) if !profile.is_empty() => {
+                let angle = cos_theta.clamp(-1.0, 1.0).acos().to_degrees();
+                let x = (angle / self.cone_angle * (profile.len() - 1) as f64).max(0.0);
+                let i = (x as usize).min(profile.len() - 1);
+                let j = (i + 1).min(profile.len() - 1);
+                let f = x - i as f64;
+                (1.0 - f) * profile[i] + f * profile[j]
+            }
+            _ => 1.0,
+        };
+        smooth * shaped
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, p: &Point3<f64>, _time: f64) -> Option<LightSample> {
+        let to_light = self.position - *p;
+        let distance = to_light.length();
+        if distance <= 0.0 {
+            return None;
+        }
+        let direction = to_light / distance;
+        let falloff = self.falloff(&-direction);
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: falloff * self.intensity / (distance * distance),
+            pdf: 1.0,
+            delta: true,
+        })
+    }
+
+    // Solid angle of the cone out to halfway through the falloff band,
+    // ignoring the profile.
+    fn power(&self) -> f64 {
+        let cos_outer = degrees_to_radians(self.cone_angle).cos();
+        let cos_inner = degrees_to_radians(self.falloff_start.min(self.cone_angle)).cos();
+        2.0 * PI * (1.0 - 0.5 * (cos_inner + cos_outer)) * luminance(&self.intensity)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.position, self.position))
+    }
+}
+
+// Parallel light from infinitely far away, such as a sun without a disk.
+// `irradiance` is measured on a surface facing the light.
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    // Direction the light travels in.
+    pub direction: Vec3<f64>,
+    pub irradiance: Color<f64>
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3<f64>, irradiance: Color<f64>) -> Self {
+        Self { direction: direction.unit_vector(), irradiance }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _p: &Point3<f64>, _time: f64) -> Option<LightSample> {
+        Some(LightSample { direction: -self.direction, distance: INFINITY, radiance: self.irradiance, pdf: 1.0, delta: true })
+    }
+
+    // Power through a unit disk facing the light, since the light has no
+    // extent of its own.
+    fn power(&self) -> f64 {
+        PI * luminance(&self.irradiance)
+    }
+}
+
+// Viewpoints, spread evenly over a sphere around the object, from which an
+// area light's mean emitted radiance is estimated.
+const POWER_PROBES: usize = 16;
+
+// Emissive object from the world, sampled by picking points on its surface
+// with `Hittable::random`. Rays that find it by scattering see its material's
+// emission as usual.
+pub struct AreaLight {
+    pub object: Arc<dyn Hittable>,
+    // Index of the object in the world list, as reported in
+    // `HitRecord::object_id`.
+    pub object_id: usize,
+    power: f64,
+    bounds: Option<Aabb>
+}
+
+impl AreaLight {
+    pub fn new(object: Arc<dyn Hittable>, object_id: usize) -> Self {
+        let mut bounds = Aabb::zero();
+        let bounds = if object.bounding_box(0.0, 1.0, &mut bounds) { Some(bounds) } else { None };
+
+        // Mean emission seen from around the object, times its area and the
+        // cosine weighted hemisphere.
+        let mut power = 0.0;
+        if let Some(bounds) = bounds {
+            let center = 0.5 * (bounds.minimum + bounds.maximum);
+            let radius = 0.5 * (bounds.maximum - bounds.minimum).length() + 1.0;
+            let mut total = 0.0;
+            let mut found = 0;
+            let mut rec = HitRecord::zero();
+            let quarter = 0.25 * (bounds.maximum - bounds.minimum);
+            for i in 0..POWER_PROBES {
+                // Spherical Fibonacci points, each looking at the center and
+                // the centers of the eight octants of the bounds.
+                let z = 1.0 - (2 * i + 1) as f64 / POWER_PROBES as f64;
+                let phi = PI * (3.0 - 5.0f64.sqrt()) * i as f64;
+                let r = (1.0 - z * z).sqrt();
+                let origin = center + 2.0 * radius * Vec3::new(r * phi.cos(), r * phi.sin(), z);
+                for octant in 0..9 {
+                    let target = if octant == 8 {
+                        center
+                    } else {
+                        let sign = |bit: usize| if octant & bit == 0 { -1.0 } else { 1.0 };
+                        center + Vec3::new(sign(1) * quarter.x, sign(2) * quarter.y, sign(4) * quarter.z)
+                    };
+                    if object.hit(Ray::new(origin, target - origin), 0.001, INFINITY, &mut rec) {
+                        total += luminance(&rec.mat_ptr.emitted(rec.u, rec.v, &rec.p));
+                        found += 1;
+                    }
+                }
+            }
+            if found > 0 {
+                power = PI * object.area() * total / found as f64;
+            }
+        }
+
+        Self { object, object_id, power, bounds }
+    }
+}
+
+impl Light for AreaLight {
+    fn sample(&self, p: &Point3<f64>, time: f64) -> Option<LightSample> {
+        if self.object.area() <= 0.0 {
+            return None;
+        }
+        let direction = self.object.random(p);
+        let mut rec = HitRecord::zero();
+        if !self.object.hit(Ray::with_time(*p, direction, time), 0.001, INFINITY, &mut rec) {
+            return None;
+        }
+        let pdf = self.object.pdf_value(p, &direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance: rec.t,
+            radiance: rec.mat_ptr.emitted(rec.u, rec.v, &rec.p),
+            pdf,
+            delta: false,
+        })
+    }
+
+    fn pdf(&self, p: &Point3<f64>, direction: &Vec3<f64>) -> f64 {
+        self.object.pdf_value(p, direction)
+    }
+
+    fn power(&self) -> f64 {
+        self.power
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::material::DiffuseLight;
+    use crate::sphere::Sphere;
+
+    #[test]
+    fn spot_light_cone_and_profile() {
+        let spot = SpotLight::new(Point3::new(0.0, 2.0, 0.0), Point3::zero(), Color::new(4.0, 4.0, 4.0), 30.0, 20.0);
+
+        // Straight below: full intensity over distance squared.
+        let below = spot.sample(&Point3::zero(), 0.0).unwrap();
+        assert!((below.radiance.x - 1.0).abs() < 1e-9);
+        assert!((below.direction - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+
+        // 25 degrees off axis is in the falloff band; 40 is outside.
+        let off_axis = |degrees: f64| Point3::new(2.0 * degrees_to_radians(degrees).tan(), 0.0, 0.0);
+        let band = spot.falloff(&(off_axis(25.0) - spot.position).unit_vector());
+        assert!(band > 0.0 && band < 1.0);
+        assert!(spot.sample(&off_axis(40.0), 0.0).is_none());
+
+        let shaped = spot.clone().with_profile(vec![1.0, 0.0]);
+        assert!((shaped.falloff(&(off_axis(15.0) - spot.position).unit_vector()) - 0.5).abs() < 1e-9);
+    }
+
+    // Emitter that only exists in the second half of the shutter.
+    struct Flash(Sphere);
+
+    impl Hittable for Flash {
+        fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+            r.time > 0.5 && self.0.hit(r, t_min, t_max, rec)
+        }
+
+        fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+            self.0.bounding_box(time0, time1, output_box)
+        }
+
+        fn area(&self) -> f64 {
+            self.0.area()
+        }
+
+        fn random(&self, origin: &Point3<f64>) -> Vec3<f64> {
+            self.0.random(origin)
+        }
+
+        fn pdf_value(&self, origin: &Point3<f64>, direction: &Vec3<f64>) -> f64 {
+            self.0.pdf_value(origin, direction)
+        }
+    }
+
+    #[test]
+    fn area_light_power_and_time() {
+        // A unit sphere of unit radiance emits pi times its area, and every
+        // probe finds it, so the estimate is exact and repeatable.
+        let glow = Arc::new(DiffuseLight::new(&Color::new(1.0, 1.0, 1.0)));
+        let sphere = Sphere::new(Point3::zero(), 1.0, glow);
+        let light = AreaLight::new(Arc::new(sphere.clone()), 0);
+        assert!((light.power() - 4.0 * PI * PI).abs() < 1e-9, "got {}", light.power());
+        assert_eq!(light.power(), AreaLight::new(Arc::new(sphere.clone()), 0).power());
+
+        // Samples are traced at the shaded ray's time.
+        let flash = AreaLight::new(Arc::new(Flash(sphere)), 0);
+        let p = Point3::new(0.0, 3.0, 0.0);
+        assert!(flash.sample(&p, 0.25).is_none());
+        assert!(flash.sample(&p, 0.75).is_some());
+    }
+}
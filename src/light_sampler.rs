@@ -0,0 +1,292 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::distribution::AliasTable;
+use crate::light::Light;
+use crate::vec3::*;
+
+// Chooses which one of the scene's lights to sample from a shading point.
+pub trait LightSampler: Send + Sync {
+    // Index of the chosen light and the probability of choosing it, from a
+    // uniform `u`. None when no light can contribute.
+    fn sample(&self, p: &Point3<f64>, u: f64) -> Option<(usize, f64)>;
+
+    // Probability that `sample` chooses `light` from `p`.
+    fn pmf(&self, p: &Point3<f64>, light: usize) -> f64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightSampling {
+    Uniform,
+    // In proportion to emitted power, whatever the shading point.
+    Power,
+    // By estimated contribution at the shading point, through a BVH over
+    // the lights.
+    Bvh,
+}
+
+impl FromStr for LightSampling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(LightSampling::Uniform),
+            "power" => Ok(LightSampling::Power),
+            "bvh" => Ok(LightSampling::Bvh),
+            _ => Err(format!("unknown light sampler: {}", s)),
+        }
+    }
+}
+
+impl LightSampling {
+    pub fn build(&self, lights: &[Arc<dyn Light>]) -> Box<dyn LightSampler> {
+        match self {
+            LightSampling::Uniform => Box::new(UniformLightSampler::new(lights.len())),
+            LightSampling::Power => Box::new(PowerLightSampler::new(lights)),
+            LightSampling::Bvh => Box::new(BvhLightSampler::new(lights)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UniformLightSampler {
+    pub count: usize
+}
+
+impl UniformLightSampler {
+    pub fn new(count: usize) -> Self {
+        Self { count }
+    }
+}
+
+impl LightSampler for UniformLightSampler {
+    fn sample(&self, _p: &Point3<f64>, u: f64) -> Option<(usize, f64)> {
+        if self.count == 0 {
+            return None;
+        }
+        Some((((u * self.count as f64) as usize).min(self.count - 1), 1.0 / self.count as f64))
+    }
+
+    fn pmf(&self, _p: &Point3<f64>, _light: usize) -> f64 {
+        if self.count == 0 { 0.0 } else { 1.0 / self.count as f64 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PowerLightSampler {
+    table: Option<AliasTable>
+}
+
+impl PowerLightSampler {
+    pub fn new(lights: &[Arc<dyn Light>]) -> Self {
+        let powers: Vec<f64> = lights.iter().map(|l| l.power()).collect();
+        Self { table: if powers.is_empty() { None } else { Some(AliasTable::new(&powers)) } }
+    }
+}
+
+impl LightSampler for PowerLightSampler {
+    fn sample(&self, _p: &Point3<f64>, u: f64) -> Option<(usize, f64)> {
+        let (light, pmf) = self.table.as_ref()?.sample(u);
+        if pmf > 0.0 { Some((light, pmf)) } else { None }
+    }
+
+    fn pmf(&self, _p: &Point3<f64>, light: usize) -> f64 {
+        self.table.as_ref().map_or(0.0, |t| t.pmf[light])
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LightNodeKind {
+    Leaf(usize),
+    Interior(usize, usize)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LightNode {
+    bounds: Aabb,
+    power: f64,
+    kind: LightNodeKind
+}
+
+impl LightNode {
+    // Rough contribution of the node's lights at `p`: power over squared
+    // distance to the center of its bounds, no closer than the bounds'
+    // radius. Emission direction is not taken into account.
+    fn importance(&self, p: &Point3<f64>) -> f64 {
+        let center = 0.5 * (self.bounds.minimum + self.bounds.maximum);
+        let radius = 0.5 * (self.bounds.maximum - self.bounds.minimum).length();
+        let distance_squared = (center - *p).length_squared();
+        self.power / distance_squared.max(radius * radius).max(1e-8)
+    }
+}
+
+// Binary tree over the bounded lights, built by median splits along the
+// longest axis of their centers. Each step down picks a child in proportion
+// to its importance at the shading point. Lights without bounds, such as
+// directional ones, are chosen uniformly alongside the tree.
+#[derive(Debug, Clone)]
+pub struct BvhLightSampler {
+    nodes: Vec<LightNode>,
+    infinite: Vec<usize>,
+    // For each light in the tree, the child taken at each level on the way to
+    // its leaf, as bits from the lowest (1 is the second child).
+    trails: Vec<Option<u64>>
+}
+
+impl BvhLightSampler {
+    pub fn new(lights: &[Arc<dyn Light>]) -> Self {
+        let mut sampler = Self { nodes: Vec::new(), infinite: Vec::new(), trails: vec![None; lights.len()] };
+        let mut bounded = Vec::new();
+        for (i, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                Some(bounds) if light.power() > 0.0 => bounded.push((i, bounds, light.power())),
+                None if light.power() > 0.0 => sampler.infinite.push(i),
+                _ => {}
+            }
+        }
+        if !bounded.is_empty() {
+            sampler.build(&mut bounded, 0, 0);
+        }
+        sampler
+    }
+
+    // Appends the subtree for `lights` and returns its index.
+    fn build(&mut self, lights: &mut [(usize, Aabb, f64)], trail: u64, depth: u32) -> usize {
+        let index = self.nodes.len();
+        if lights.len() == 1 || depth == 63 {
+            let (light, bounds, power) = lights[0];
+            self.trails[light] = Some(trail);
+            self.nodes.push(LightNode { bounds, power, kind: LightNodeKind::Leaf(light) });
+            return index;
+        }
+
+        let centroid = |b: &Aabb| 0.5 * (b.minimum + b.maximum);
+        let (mut lo, mut hi) = (centroid(&lights[0].1), centroid(&lights[0].1));
+        let mut bounds = lights[0].1;
+        for (_, b, _) in lights.iter() {
+            let c = centroid(b);
+            lo = Point3::new(lo.x.min(c.x), lo.y.min(c.y), lo.z.min(c.z));
+            hi = Point3::new(hi.x.max(c.x), hi.y.max(c.y), hi.z.max(c.z));
+            bounds = surrounding_box(&bounds, b);
+        }
+        let extent = hi - lo;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+        let key = |b: &Aabb| { let c = centroid(b); [c.x, c.y, c.z][axis] };
+        lights.sort_by(|a, b| key(&a.1).total_cmp(&key(&b.1)));
+
+        // Reserve this node; children follow it.
+        self.nodes.push(LightNode { bounds, power: lights.iter().map(|l| l.2).sum(), kind: LightNodeKind::Leaf(0) });
+        let (left, right) = lights.split_at_mut(lights.len() / 2);
+        let left = self.build(left, trail, depth + 1);
+        let right = self.build(right, trail | (1 << depth), depth + 1);
+        self.nodes[index].kind = LightNodeKind::Interior(left, right);
+        index
+    }
+
+    // Chance of choosing among the unbounded lights instead of the tree.
+    fn infinite_probability(&self) -> f64 {
+        let tree = if self.nodes.is_empty() { 0.0 } else { 1.0 };
+        let n = self.infinite.len() as f64;
+        if n + tree == 0.0 { 0.0 } else { n / (n + tree) }
+    }
+
+    // Probability of taking the left child of an interior node at `p`.
+    fn left_probability(&self, left: usize, right: usize, p: &Point3<f64>) -> Option<f64> {
+        let (l, r) = (self.nodes[left].importance(p), self.nodes[right].importance(p));
+        if l + r > 0.0 { Some(l / (l + r)) } else { None }
+    }
+}
+
+impl LightSampler for BvhLightSampler {
+    fn sample(&self, p: &Point3<f64>, u: f64) -> Option<(usize, f64)> {
+        let p_infinite = self.infinite_probability();
+        if u < p_infinite {
+            let n = self.infinite.len();
+            let i = ((u / p_infinite * n as f64) as usize).min(n - 1);
+            return Some((self.infinite[i], p_infinite / n as f64));
+        }
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut u = ((u - p_infinite) / (1.0 - p_infinite)).min(1.0 - f64::EPSILON);
+        let mut pmf = 1.0 - p_infinite;
+        let mut node = 0;
+        loop {
+            match self.nodes[node].kind {
+                LightNodeKind::Leaf(light) => return Some((light, pmf)),
+                LightNodeKind::Interior(left, right) => {
+                    let p_left = self.left_probability(left, right, p)?;
+                    if u < p_left {
+                        u /= p_left;
+                        pmf *= p_left;
+                        node = left;
+                    } else {
+                        u = ((u - p_left) / (1.0 - p_left)).min(1.0 - f64::EPSILON);
+                        pmf *= 1.0 - p_left;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    fn pmf(&self, p: &Point3<f64>, light: usize) -> f64 {
+        let p_infinite = self.infinite_probability();
+        if self.infinite.contains(&light) {
+            return p_infinite / self.infinite.len() as f64;
+        }
+        let Some(mut trail) = self.trails.get(light).copied().flatten() else { return 0.0; };
+
+        let mut pmf = 1.0 - p_infinite;
+        let mut node = 0;
+        while let LightNodeKind::Interior(left, right) = self.nodes[node].kind {
+            let Some(p_left) = self.left_probability(left, right, p) else { return 0.0; };
+            if trail & 1 == 0 {
+                pmf *= p_left;
+                node = left;
+            } else {
+                pmf *= 1.0 - p_left;
+                node = right;
+            }
+            trail >>= 1;
+        }
+        pmf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+    use crate::light::{DirectionalLight, PointLight};
+
+    #[test]
+    fn samplers_agree_with_their_pmfs() {
+        let mut lights: Vec<Arc<dyn Light>> = Vec::new();
+        for i in 0..10 {
+            let position = Point3::new(i as f64 * 10.0, 0.0, 0.0);
+            lights.push(Arc::new(PointLight::new(position, Color::new(1.0, 1.0, 1.0) * (i + 1) as f64)));
+        }
+        lights.push(Arc::new(DirectionalLight::new(Vec3::new(0.0, -1.0, 0.0), Color::new(1.0, 1.0, 1.0))));
+
+        let p = Point3::new(1.0, 1.0, 0.0);
+        for sampling in [LightSampling::Uniform, LightSampling::Power, LightSampling::Bvh] {
+            let sampler = sampling.build(&lights);
+            let total: f64 = (0..lights.len()).map(|i| sampler.pmf(&p, i)).sum();
+            assert!((total - 1.0).abs() < 1e-9, "{:?} sums to {}", sampling, total);
+            for k in 0..100 {
+                let (light, pmf) = sampler.sample(&p, (k as f64 + 0.5) / 100.0).unwrap();
+                assert!((pmf - sampler.pmf(&p, light)).abs() < 1e-9);
+            }
+        }
+
+        // Power favours the brightest light; the BVH the nearest.
+        let power = LightSampling::Power.build(&lights);
+        assert!(power.pmf(&p, 9) > power.pmf(&p, 0));
+        let bvh = LightSampling::Bvh.build(&lights);
+        assert!(bvh.pmf(&p, 0) > bvh.pmf(&p, 9));
+        assert!(bvh.pmf(&p, 0) > 0.25);
+    }
+}
@@ -1,40 +1,25 @@
 use raychasing::color::*;
 use raychasing::vec3::*;
-use raychasing::ray::*;
-use raychasing::constants::*;
 use raychasing::utilities::*;
 use raychasing::hittable_list::*;
-use raychasing::hittable::*;
 use raychasing::sphere::*;
+use raychasing::plane::*;
+use raychasing::constant_medium::*;
 use raychasing::camera::*;
 use raychasing::material::*;
+use raychasing::integrator::*;
+use raychasing::film::*;
+use raychasing::denoise::*;
+use raychasing::spectrum::*;
+use raychasing::environment::*;
+use raychasing::scene::*;
+use raychasing::sky::*;
+use raychasing::light_sampler::*;
+use raychasing::stereo::*;
 
 use std::io::{self, Write};
 use std::sync::Arc;
 
-fn ray_color<'a>(r: Ray<f64>, world: &'a HittableList, depth: i32) -> Color<f64> 
-{
-    let mut rec = HitRecord::zero();
-
-    // If we've exceeded the ray bounce limit, no more light is gathered.
-    if depth <= 0 {
-        return Color::zero();
-    }
-
-    if world.hit(r, 0.001, INFINITY, &mut rec) {
-        let mut scattered = Ray::x_unit();
-        let mut attenuation = Vec3::new(1.0, 1.0, 1.0);
-        if rec.mat_ptr.as_ref().scatter( &r, &rec, &mut attenuation, &mut scattered) {
-            return attenuation * ray_color(scattered, world, depth - 1);
-        }
-        return Color::zero();
-    }
-
-    let unit_direction = r.direction.unit_vector();
-    let t = 0.5 * (unit_direction.y + 1.0);
-    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
-}
-
 fn random_scene() -> HittableList<'static> {
     let mut world = HittableList::<'static>::new();
 
@@ -42,7 +27,7 @@ fn random_scene() -> HittableList<'static> {
         &Color::new(0.5, 0.5, 0.5)
      ));
 
-     world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground_material));
+     world.add(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), ground_material));
 
      for a in -2..2 {
         for b in -2..2 {
@@ -83,9 +68,85 @@ fn random_scene() -> HittableList<'static> {
 }
 
 
+fn invalid_input<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
 fn main() -> io::Result<()> {
+    // Options
+    let mut debug_mode = None;
+    let mut exr_path = None;
+    let mut pfm_prefix = None;
+    let mut denoised_path = None;
+    let mut fog_density = None;
+    let mut spectral = false;
+    let mut env_path = None;
+    let mut env_rotation = 0.0;
+    let mut env_intensity = 1.0;
+    // The scene is lit by the physical sky unless --gradient brings back the
+    // original backdrop.
+    let mut sun_elevation = Some(45.0);
+    let mut sun_azimuth = 0.0;
+    let mut turbidity = 3.0;
+    let mut light_sampling = LightSampling::Bvh;
+    let mut ortho_width = None;
+    let mut panorama = None;
+    let mut stereo_ipd = None;
+    let mut stereo_layout = StereoLayout::SideBySide;
+    let mut focal_length = None;
+    let mut f_number = 8.0;
+    let mut shutter = 1.0 / 125.0;
+    let mut iso = 100.0;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| invalid_input(format!("missing value for {}", arg)));
+        match arg.as_str() {
+            "--debug" => debug_mode = Some(value()?.parse::<DebugMode>().map_err(invalid_input)?),
+            "--exr" => exr_path = Some(value()?),
+            "--pfm" => pfm_prefix = Some(value()?),
+            "--denoise" => denoised_path = Some(value()?),
+            "--spectral" => spectral = true,
+            "--env" => env_path = Some(value()?),
+            "--env-rotation" => env_rotation = value()?.parse::<f64>().map_err(invalid_input)?,
+            "--env-intensity" => env_intensity = value()?.parse::<f64>().map_err(invalid_input)?,
+            "--sky" => sun_elevation = Some(value()?.parse::<f64>().map_err(invalid_input)?),
+            "--gradient" => sun_elevation = None,
+            "--sun-azimuth" => sun_azimuth = value()?.parse::<f64>().map_err(invalid_input)?,
+            "--turbidity" => turbidity = value()?.parse::<f64>().map_err(invalid_input)?,
+            "--light-sampler" => light_sampling = value()?.parse::<LightSampling>().map_err(invalid_input)?,
+            "--ortho" => ortho_width = Some(value()?.parse::<f64>().map_err(invalid_input)?),
+            "--panorama" => panorama = Some(value()?),
+            "--stereo" => stereo_ipd = Some(value()?.parse::<f64>().map_err(invalid_input)?),
+            "--stereo-layout" => stereo_layout = value()?.parse::<StereoLayout>().map_err(invalid_input)?,
+            "--focal-length" => focal_length = Some(value()?.parse::<f64>().map_err(invalid_input)?),
+            "--f-number" => f_number = value()?.parse::<f64>().map_err(invalid_input)?,
+            "--shutter" => shutter = value()?.parse::<f64>().map_err(invalid_input)?,
+            "--iso" => iso = value()?.parse::<f64>().map_err(invalid_input)?,
+            "--fog" => fog_density = Some(value()?.parse::<f64>().map_err(invalid_input)?),
+            _ => return Err(invalid_input(format!("unknown argument: {}", arg))),
+        }
+    }
+    // Debug renders don't fill the albedo, normal and position guides.
+    if debug_mode.is_some() && denoised_path.is_some() {
+        return Err(invalid_input("--denoise cannot be combined with --debug"));
+    }
+    let write_aovs = exr_path.is_some() || pfm_prefix.is_some() || denoised_path.is_some();
+    let physical = focal_length.map(|focal_length| PhysicalCamera::new(focal_length, f_number, shutter, iso));
+
     // Image
-    let aspect_ratio = 3.0 / 2.0;
+    // Equirectangular panoramas need two by one, and cube maps three square
+    // faces by two, for each eye; stereo frames then hold two of those.
+    let panorama_aspect_ratio = match panorama.as_deref() {
+        Some("equirect") => Some(2.0),
+        Some("cubemap") => Some(3.0 / 2.0),
+        _ => None,
+    };
+    let aspect_ratio = match (panorama_aspect_ratio, stereo_ipd, stereo_layout) {
+        (None, _, _) => 3.0 / 2.0,
+        (Some(eye), None, _) => eye,
+        (Some(eye), Some(_), StereoLayout::SideBySide) => eye * 2.0,
+        (Some(eye), Some(_), StereoLayout::TopBottom) => eye / 2.0,
+    };
     let image_width = 400;
     let image_height = (image_width as f64 / aspect_ratio) as i32;
     let samples_per_pixel = 200;
@@ -93,7 +154,29 @@ fn main() -> io::Result<()> {
 
     // World
 
-    let world = random_scene();
+    let mut world = random_scene();
+    if let Some(density) = fog_density {
+        let boundary = Arc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 50.0, Arc::new(Dielectric::new(1.0))));
+        world.add(ConstantMedium::new(boundary, density, Color::new(1.0, 1.0, 1.0)));
+    }
+    let mut scene = Scene::new(world).with_light_sampling(light_sampling);
+    // A physical camera expects cd/m^2 rather than the sky's kcd/m^2. Other
+    // environments have no units, so they are brightened as much as the sky
+    // is over its default to read as daylight too.
+    let daylight = if physical.is_some() { 1000.0 / SKY_INTENSITY } else { 1.0 };
+    if let Some(path) = env_path {
+        if physical.is_some() {
+            io::stderr().write_all(format!("Scaling --env by {} to daylight for --focal-length\n", daylight).as_bytes())?;
+        }
+        let map = EnvironmentMap::load(path)?.with_rotation(env_rotation).with_intensity(env_intensity * daylight);
+        scene = scene.with_environment(Arc::new(map));
+    } else if let Some(elevation) = sun_elevation {
+        let sky = PhysicalSky::new(elevation, sun_azimuth, turbidity);
+        let sky = if physical.is_some() { sky.with_intensity(1000.0) } else { sky };
+        scene = scene.with_environment(Arc::new(sky));
+    } else {
+        scene = scene.with_environment(Arc::new(GradientSky::default().with_intensity(daylight)));
+    }
 
 
     // Camera
@@ -104,26 +187,84 @@ fn main() -> io::Result<()> {
     let dist_to_focus = (lookfrom - lookat).length();
     let aperture = 0.05; // 2.0;
 
-    let cam = Camera::new(lookfrom, lookat, vup, 20.0, aspect_ratio, aperture, dist_to_focus);
+    // A stereo frame holds two eyes.
+    let eye_aspect_ratio = match (stereo_ipd, stereo_layout) {
+        (None, _) => aspect_ratio,
+        (Some(_), StereoLayout::SideBySide) => aspect_ratio / 2.0,
+        (Some(_), StereoLayout::TopBottom) => aspect_ratio * 2.0,
+    };
+    let cam = match (panorama.as_deref(), ortho_width) {
+        (Some("equirect"), _) => Camera::equirectangular(lookfrom, lookat, vup),
+        (Some("cubemap"), _) => Camera::cube_map(lookfrom, lookat, vup),
+        (Some("fisheye"), _) => Camera::fisheye(lookfrom, lookat, vup, 180.0, eye_aspect_ratio, FisheyeMapping::Equidistant),
+        (Some("equisolid"), _) => Camera::fisheye(lookfrom, lookat, vup, 180.0, eye_aspect_ratio, FisheyeMapping::Equisolid),
+        (Some(other), _) => return Err(invalid_input(format!("unknown panorama: {}", other))),
+        (None, Some(width)) => Camera::orthographic(lookfrom, lookat, vup, width, eye_aspect_ratio),
+        (None, None) => match physical {
+            Some(physical) => physical.camera(lookfrom, lookat, vup, eye_aspect_ratio, dist_to_focus),
+            None => Camera::new(lookfrom, lookat, vup, 20.0, eye_aspect_ratio, aperture, dist_to_focus),
+        },
+    };
+    // Physical cameras keep their shutter speed; others expose the whole frame,
+    // from time 0 to 1, so anything animated over it is motion blurred.
+    let cam = if physical.is_some() { cam } else { cam.with_shutter(0.0, 1.0) };
+    let rig = stereo_ipd.map(|ipd| StereoRig::new(&cam, ipd, dist_to_focus, stereo_layout));
 
     // Render
 
-    println!("P3\n{} {}\n255\n", image_width, image_height);
+    let mut film = Film::new(image_width as usize, image_height as usize);
 
     for j in (0..image_height).rev() {
         io::stderr().write_all(format!("\rScanlines remaining: {}", j).as_bytes())?;
+        let y = (image_height - 1 - j) as usize;
         for i in 0..image_width {
-            let mut pixel_color = Color::new(0.0, 0.0, 0.0);
+            let x = i as usize;
             for _ in 0..samples_per_pixel {
                 let u = (i as f64 + rand_double()) / (image_width as f64 - 1.0);
                 let v = (j as f64 + rand_double()) / (image_height as f64 - 1.0);
-                let r = cam.get_ray(u, v);
-                pixel_color += ray_color(r, &world, max_depth);
+                let r = match &rig {
+                    Some(rig) => rig.get_ray(u, v),
+                    None => cam.get_ray(u, v),
+                };
+                let Some(mut r) = r else { continue; };
+                if spectral {
+                    r.wavelength = sample_wavelength();
+                }
+                let pixel_color = match debug_mode {
+                    Some(mode) => debug_color(r, &scene.world, mode),
+                    None if write_aovs => {
+                        let (color, aovs) = ray_color_aovs(r, &scene, max_depth);
+                        for (name, value) in aovs.channels() {
+                            film.add(name, x, y, value);
+                        }
+                        color
+                    }
+                    None => ray_color(r, &scene, max_depth),
+                };
+                film.add("beauty", x, y, pixel_color);
             }
-            write_color(&mut io::stdout(), pixel_color, samples_per_pixel)?;
         }
     }
-    io::stderr().write_all(format!("\nDone.\n").as_bytes())?;
+    film.resolve(samples_per_pixel);
+    // Debug views show raw values, whatever the camera.
+    if let (Some(physical), None) = (physical, debug_mode) {
+        for name in ["beauty", "diffuse_direct", "diffuse_indirect", "specular_direct", "specular_indirect"] {
+            film.scale(name, physical.exposure());
+        }
+    }
+
+    film.write_ppm(&mut io::BufWriter::new(io::stdout()), "beauty")?;
+    if let Some(path) = denoised_path {
+        Denoiser::default().apply(&mut film, "beauty", "denoised")?;
+        film.write_ppm(&mut io::BufWriter::new(std::fs::File::create(path)?), "denoised")?;
+    }
+    if let Some(path) = exr_path {
+        film.write_exr(path, "beauty")?;
+    }
+    if let Some(prefix) = pfm_prefix {
+        film.write_pfm_files(&prefix)?;
+    }
+    io::stderr().write_all(b"\nDone.\n")?;
 
     Ok(())
 }
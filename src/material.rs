@@ -1,13 +1,80 @@
+use std::sync::Arc;
+
 use crate::hittable::*;
 use crate::ray::*;
 use crate::color::*;
+use crate::constants::PI;
+use crate::spectrum::blackbody_color;
+use crate::texture::Texture;
+use crate::thin_film::*;
 use crate::utilities::rand_double;
-use crate::vec3::Vec3;
+use crate::vec3::{Point3, Vec3};
+use crate::vec3::orthonormal_basis;
 use crate::vec3::random_in_unit_sphere;
 use crate::vec3::random_unit_vector;
+use crate::volume::VolumeField;
+
+// Kind of lobe a scattered direction was sampled from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lobe {
+    // Spread over a solid angle, with density `scattering_pdf`.
+    Diffuse,
+    // Mirror or refraction directions, which light sampling never finds.
+    Specular
+}
 
-pub trait Material {
+pub trait Material: Send + Sync {
     fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool;
+
+    // As `scatter`, also telling which kind of lobe the direction came from.
+    // Materials with both kinds override this and scatter through it.
+    fn scatter_lobe(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> Option<Lobe> {
+        if !self.scatter(r_in, rec, attenuation, scattered) {
+            return None;
+        }
+        Some(if self.is_specular() { Lobe::Specular } else { Lobe::Diffuse })
+    }
+
+    // Short type name, used to tell materials apart in debug renders.
+    fn name(&self) -> &'static str {
+        "material"
+    }
+
+    // Whether scattering only follows mirror or refraction lobes. Lights are
+    // not sampled directly at such hits.
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn emitted(&self, _u: f64, _v: f64, _p: &Point3<f64>) -> Color<f64> {
+        Color::zero()
+    }
+
+    // Solid angle density with which `scatter` would choose `scattered` from
+    // its diffuse lobes. Directions with zero density are skipped when
+    // sampling lights directly.
+    fn scattering_pdf(&self, _r_in: &Ray<f64>, _rec: &HitRecord<f64>, _scattered: &Ray<f64>) -> f64 {
+        0.0
+    }
+
+    // BSDF times the cosine term towards `scattered`, over the diffuse lobes
+    // only, for light sampled directly.
+    fn eval(&self, _r_in: &Ray<f64>, _rec: &HitRecord<f64>, _scattered: &Ray<f64>) -> Color<f64> {
+        Color::zero()
+    }
+
+    // Probability that the surface is there at this hit, below one where it
+    // is partly cut away.
+    fn opacity(&self, _r_in: &Ray<f64>, _rec: &HitRecord<f64>) -> f64 {
+        1.0
+    }
+
+    // Whether the surface is cut away at this hit, so rays pass straight
+    // through as if nothing was hit. Decided once per hit from `opacity`.
+    fn is_transparent(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> bool {
+        let opacity = self.opacity(r_in, rec);
+        opacity < 1.0 && rand_double() >= opacity
+    }
 }
 
 pub struct Lambertian {
@@ -21,72 +88,746 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
         let mut scatter_direction = rec.normal + random_unit_vector();
 
         // Catch degenerate scatter direction
         if scatter_direction.near_zero() {
             scatter_direction = rec.normal
         }
-        *scattered = Ray::new(rec.p, scatter_direction);
+        *scattered = Ray::with_time(rec.p, scatter_direction, r_in.time);
         *attenuation = self.albedo;
-        return true;
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "lambertian"
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> f64 {
+        let cosine = rec.normal.dot(scattered.direction.unit_vector());
+        if cosine < 0.0 { 0.0 } else { cosine / PI }
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        self.scattering_pdf(r_in, rec, scattered) * self.albedo
     }
 }
 
 pub struct Metal {
     pub albedo: Color<f64>,
-    pub fuzz: f64
+    pub fuzz: f64,
+    pub film: Option<ThinFilm>
 }
 
 impl Metal {
     pub fn new(a: &Color<f64>, f: f64) -> Self {
-        Self{albedo: *a, fuzz: f}
+        Self{albedo: *a, fuzz: f, film: None}
+    }
+
+    // Oxide or oil layer giving tempered steel and anodized colors.
+    pub fn with_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
     }
 }
 
 impl Material for Metal {
     fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
         let reflected = r_in.direction.unit_vector().reflect(rec.normal);
-        *scattered = Ray::new(rec.p, reflected + self.fuzz * random_in_unit_sphere());
-        *attenuation = self.albedo;
+        *scattered = Ray::with_time(rec.p, reflected + self.fuzz * random_in_unit_sphere(), r_in.time);
+        *attenuation = match self.film {
+            Some(film) => {
+                let cos_i = (-r_in.direction.unit_vector().dot(rec.normal)).clamp(0.0, 1.0);
+                per_channel(r_in.wavelength, self.albedo, |lambda, base| film.reflectance_conductor(cos_i, 1.0, base, lambda))
+            }
+            None => self.albedo
+        };
         scattered.direction.dot(rec.normal) > 0.0
     }
+
+    fn name(&self) -> &'static str {
+        "metal"
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+
+
+// Wavelength dependent index of refraction, with wavelengths in micrometers
+// as in published glass catalogs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispersion {
+    // n = a + b / lambda^2
+    Cauchy { a: f64, b: f64 },
+    // n^2 = 1 + sum of b_i lambda^2 / (lambda^2 - c_i)
+    Sellmeier { b: [f64; 3], c: [f64; 3] }
 }
 
+impl Dispersion {
+    // Schott N-BK7 borosilicate crown glass.
+    pub const BK7: Dispersion = Dispersion::Sellmeier {
+        b: [1.03961212, 0.231792344, 1.01046945],
+        c: [0.00600069867, 0.0200179144, 103.560653]
+    };
+    // Schott SF11 dense flint glass.
+    pub const SF11: Dispersion = Dispersion::Sellmeier {
+        b: [1.73759695, 0.313747346, 1.89878101],
+        c: [0.013188707, 0.0623068142, 155.23629]
+    };
+
+    // Wavelength of the helium d line, where catalogs quote a single index.
+    pub const D_LINE: f64 = 587.56;
 
+    pub fn ior(&self, lambda_nm: f64) -> f64 {
+        let l2 = (lambda_nm * 1e-3).powi(2);
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / l2,
+            Dispersion::Sellmeier { b, c } => {
+                (1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>()).sqrt()
+            }
+        }
+    }
+}
 
 pub struct Dielectric {
-    pub index_of_refraction: f64
+    pub index_of_refraction: f64,
+    // Beer-Lambert absorption coefficient per unit length, per channel.
+    pub absorption: Color<f64>,
+    // Only used by spectral paths; RGB paths see `index_of_refraction`.
+    pub dispersion: Option<Dispersion>,
+    pub film: Option<ThinFilm>
 }
 
 impl Dielectric {
     pub fn new(index_of_refraction: f64) -> Self {
-        Self{index_of_refraction: index_of_refraction}
+        Self{index_of_refraction, absorption: Color::zero(), dispersion: None, film: None}
+    }
+
+    // Colored glass that lets `transmittance` of the light through after
+    // travelling `distance` inside it, which is easier to art direct than a
+    // raw coefficient.
+    pub fn with_transmittance(index_of_refraction: f64, transmittance: &Color<f64>, distance: f64) -> Self {
+        let sigma = |t: f64| -t.max(1e-6).ln() / distance;
+        let absorption = Color::new(sigma(transmittance.x), sigma(transmittance.y), sigma(transmittance.z));
+        Self{index_of_refraction, absorption, dispersion: None, film: None}
+    }
+
+    pub fn with_dispersion(mut self, dispersion: Dispersion) -> Self {
+        self.index_of_refraction = dispersion.ior(Dispersion::D_LINE);
+        self.dispersion = Some(dispersion);
+        self
+    }
+
+    // Soap bubbles are a film over Dielectric::new(1.0).
+    pub fn with_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
+    }
+
+    pub fn ior(&self, wavelength: f64) -> f64 {
+        match self.dispersion {
+            Some(d) if wavelength > 0.0 => d.ior(wavelength),
+            _ => self.index_of_refraction
+        }
     }
 }
 
 impl Material for Dielectric {
     fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
         *attenuation = Color::new(1.0, 1.0, 1.0);
-        let refraction_ratio = if rec.front_face { 1.0 / self.index_of_refraction} else { self.index_of_refraction};
+        if !rec.front_face {
+            // The ray has travelled through the interior to reach a back face.
+            let distance = rec.t * r_in.direction.length();
+            let a = self.absorption;
+            *attenuation = Color::new((-a.x * distance).exp(), (-a.y * distance).exp(), (-a.z * distance).exp());
+        }
+        let ior = self.ior(r_in.wavelength);
+        let refraction_ratio = if rec.front_face { 1.0 / ior } else { ior };
         
         let unit_direction = r_in.direction.unit_vector();
         let cos_theta = (-unit_direction.dot(rec.normal)).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let direction;
+        let direction = if cannot_refract {
+            unit_direction.reflect(rec.normal)
+        } else if let Some(film) = self.film {
+            let (p, reflected, refracted) = film_lobes(&film, cos_theta, rec.front_face, ior, r_in.wavelength);
+            if rand_double() < p {
+                *attenuation = *attenuation * reflected;
+                unit_direction.reflect(rec.normal)
+            } else {
+                *attenuation = *attenuation * refracted;
+                unit_direction.refract(rec.normal, refraction_ratio)
+            }
+        } else if reflectance(cos_theta, refraction_ratio) > rand_double() {
+            unit_direction.reflect(rec.normal)
+        } else {
+            unit_direction.refract(rec.normal, refraction_ratio)
+        };
+
+        *scattered = Ray::with_time(rec.p, direction, r_in.time);
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "dielectric"
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+// Probability of reflecting off a filmed dielectric, with the weights of the
+// reflected and refracted paths. Reflectance differs per channel, so a lobe is
+// picked by the average and each channel reweighted by its share.
+fn film_lobes(film: &ThinFilm, cos_theta: f64, front_face: bool, ior: f64, wavelength: f64) -> (f64, Color<f64>, Color<f64>) {
+    let (eta_i, eta_t) = if front_face { (1.0, ior) } else { (ior, 1.0) };
+    let reflectance = per_channel(wavelength, Color::new(1.0, 1.0, 1.0), |lambda, _| {
+        film.reflectance_dielectric(cos_theta, eta_i, eta_t, lambda)
+    });
+    let p = ((reflectance.x + reflectance.y + reflectance.z) / 3.0).clamp(1e-6, 1.0 - 1e-6);
+    (p, reflectance / p, (1.0 - reflectance) / (1.0 - p))
+}
+
+// Clear dielectric layer over any base material, as on car paint or
+// varnished wood. Each hit either reflects off the coating, with Fresnel
+// probability, or passes through it to the base and back out. Light that
+// would be reflected back down on the way out is dropped rather than traced,
+// so the layers never add energy.
+pub struct Coated {
+    pub base: Arc<dyn Material>,
+    pub ior: f64,
+    // Fuzz of the coating's reflection, as for Metal.
+    pub roughness: f64,
+    // Fraction of light the coating lets through on one pass at normal
+    // incidence; passes at grazing angles travel further and absorb more.
+    pub tint: Color<f64>
+}
+
+impl Coated {
+    pub fn new(base: Arc<dyn Material>, ior: f64, roughness: f64) -> Self {
+        Self{base, ior, roughness, tint: Color::new(1.0, 1.0, 1.0)}
+    }
+
+    pub fn with_tint(mut self, tint: Color<f64>) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    // Share of the light the base sends out along `cos_out` that got through
+    // the coating on the way in along `cos_in` and back out again.
+    fn transmitted(&self, cos_in: f64, cos_out: f64) -> Color<f64> {
+        let path = 1.0 / cos_in + 1.0 / cos_out;
+        let t = self.tint;
+        let absorbed = Color::new(t.x.powf(path), t.y.powf(path), t.z.powf(path));
+        (1.0 - reflectance(cos_out, 1.0 / self.ior)) * absorbed
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+        self.scatter_lobe(r_in, rec, attenuation, scattered).is_some()
+    }
 
-        if cannot_refract || reflectance(cos_theta, refraction_ratio) > rand_double() {
-            direction = unit_direction.reflect(rec.normal)
+    fn scatter_lobe(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> Option<Lobe> {
+        // The coating is only on the outside.
+        if !rec.front_face {
+            return self.base.scatter_lobe(r_in, rec, attenuation, scattered);
+        }
+
+        let unit_direction = r_in.direction.unit_vector();
+        let cos_in = (-unit_direction.dot(rec.normal)).clamp(1e-4, 1.0);
+        if rand_double() < reflectance(cos_in, 1.0 / self.ior) {
+            let reflected = unit_direction.reflect(rec.normal);
+            *scattered = Ray::with_time(rec.p, reflected + self.roughness * random_in_unit_sphere(), r_in.time);
+            *attenuation = Color::new(1.0, 1.0, 1.0);
+            return if scattered.direction.dot(rec.normal) > 0.0 { Some(Lobe::Specular) } else { None };
+        }
+
+        let lobe = self.base.scatter_lobe(r_in, rec, attenuation, scattered)?;
+        let cos_out = scattered.direction.unit_vector().dot(rec.normal);
+        if cos_out <= 0.0 {
+            return None;
+        }
+        *attenuation = *attenuation * self.transmitted(cos_in, cos_out);
+        Some(lobe)
+    }
+
+    fn name(&self) -> &'static str {
+        "coated"
+    }
+
+    fn is_specular(&self) -> bool {
+        self.base.is_specular()
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: &Point3<f64>) -> Color<f64> {
+        self.base.emitted(u, v, p)
+    }
+
+    fn opacity(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> f64 {
+        self.base.opacity(r_in, rec)
+    }
+
+    // Only light passing through the coating reaches the base.
+    fn scattering_pdf(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> f64 {
+        let base = self.base.scattering_pdf(r_in, rec, scattered);
+        if !rec.front_face {
+            return base;
+        }
+        let cos_in = (-r_in.direction.unit_vector().dot(rec.normal)).clamp(1e-4, 1.0);
+        (1.0 - reflectance(cos_in, 1.0 / self.ior)) * base
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        let base = self.base.eval(r_in, rec, scattered);
+        if !rec.front_face {
+            return base;
+        }
+        let cos_in = (-r_in.direction.unit_vector().dot(rec.normal)).clamp(1e-4, 1.0);
+        let cos_out = scattered.direction.unit_vector().dot(rec.normal);
+        if cos_out <= 0.0 {
+            return Color::zero();
+        }
+        (1.0 - reflectance(cos_in, 1.0 / self.ior)) * self.transmitted(cos_in, cos_out) * base
+    }
+}
+
+// How much of the second material shows through in a `Mix`.
+#[derive(Clone)]
+pub enum MixWeight {
+    Constant(f64),
+    // Mask read from the texture's first channel, e.g. painted rust.
+    Texture(Arc<dyn Texture>),
+    // Schlick Fresnel reflectance of a dielectric with this index, so the
+    // second material takes over at grazing angles.
+    Fresnel(f64),
+    // (1 - cos theta)^exponent, a cheaper artistic rim falloff.
+    FacingRatio(f64)
+}
+
+impl MixWeight {
+    // View dependent weights fall back to normal incidence when there is no
+    // ray, as for emission.
+    fn value(&self, cos_theta: f64, u: f64, v: f64, p: &Point3<f64>) -> f64 {
+        let w = match self {
+            MixWeight::Constant(w) => *w,
+            MixWeight::Texture(mask) => mask.value(u, v, p).x,
+            MixWeight::Fresnel(ior) => reflectance(cos_theta, 1.0 / ior),
+            MixWeight::FacingRatio(exponent) => (1.0 - cos_theta).powf(*exponent),
+        };
+        w.clamp(0.0, 1.0)
+    }
+}
+
+// Picks `a` or `b` at random per hit, choosing `b` with probability given by
+// `weight`. Either child may itself be a Mix.
+pub struct Mix {
+    pub a: Arc<dyn Material>,
+    pub b: Arc<dyn Material>,
+    pub weight: MixWeight
+}
+
+impl Mix {
+    pub fn new(a: Arc<dyn Material>, b: Arc<dyn Material>, weight: MixWeight) -> Self {
+        Self{a, b, weight}
+    }
+
+    // Weights of `a` and `b` at this hit, each times its child's opacity.
+    fn opaque_weights(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> (f64, f64) {
+        let cos_theta = (-r_in.direction.unit_vector().dot(rec.normal)).clamp(0.0, 1.0);
+        let w = self.weight.value(cos_theta, rec.u, rec.v, &rec.p);
+        ((1.0 - w) * self.a.opacity(r_in, rec), w * self.b.opacity(r_in, rec))
+    }
+
+    // Probability of shading with `b`. A hit that was kept landed on an
+    // opaque part, so a child that is cut away there is less likely.
+    fn b_weight(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> f64 {
+        let (a, b) = self.opaque_weights(r_in, rec);
+        if a + b > 0.0 { b / (a + b) } else { 0.0 }
+    }
+}
+
+impl Material for Mix {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+        self.scatter_lobe(r_in, rec, attenuation, scattered).is_some()
+    }
+
+    fn scatter_lobe(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> Option<Lobe> {
+        if rand_double() < self.b_weight(r_in, rec) {
+            self.b.scatter_lobe(r_in, rec, attenuation, scattered)
         } else {
-            direction = unit_direction.refract(rec.normal, refraction_ratio)
+            self.a.scatter_lobe(r_in, rec, attenuation, scattered)
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "mix"
+    }
+
+    fn is_specular(&self) -> bool {
+        self.a.is_specular() && self.b.is_specular()
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: &Point3<f64>) -> Color<f64> {
+        let w = self.weight.value(1.0, u, v, p);
+        (1.0 - w) * self.a.emitted(u, v, p) + w * self.b.emitted(u, v, p)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> f64 {
+        let w = self.b_weight(r_in, rec);
+        (1.0 - w) * self.a.scattering_pdf(r_in, rec, scattered) + w * self.b.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        let w = self.b_weight(r_in, rec);
+        (1.0 - w) * self.a.eval(r_in, rec, scattered) + w * self.b.eval(r_in, rec, scattered)
+    }
+
+    fn opacity(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> f64 {
+        let (a, b) = self.opaque_weights(r_in, rec);
+        a + b
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    // Opaque with probability alpha, which averages to soft edges.
+    Stochastic,
+    // Opaque where alpha is at least the cutoff, for crisp edges.
+    Threshold(f64)
+}
+
+// Wraps a material with an alpha mask, read from the texture's first
+// channel, for leaves, fences and decals modelled as simple shapes.
+pub struct Cutout {
+    pub material: Arc<dyn Material>,
+    pub alpha: Arc<dyn Texture>,
+    pub mode: AlphaMode
+}
+
+impl Cutout {
+    pub fn new(material: Arc<dyn Material>, alpha: Arc<dyn Texture>, mode: AlphaMode) -> Self {
+        Self{material, alpha, mode}
+    }
+}
+
+impl Material for Cutout {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+        self.material.scatter(r_in, rec, attenuation, scattered)
+    }
+
+    fn scatter_lobe(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> Option<Lobe> {
+        self.material.scatter_lobe(r_in, rec, attenuation, scattered)
+    }
+
+    fn name(&self) -> &'static str {
+        self.material.name()
+    }
+
+    fn is_specular(&self) -> bool {
+        self.material.is_specular()
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: &Point3<f64>) -> Color<f64> {
+        self.material.emitted(u, v, p)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> f64 {
+        self.material.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        self.material.eval(r_in, rec, scattered)
+    }
+
+    fn opacity(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> f64 {
+        let alpha = self.alpha.value(rec.u, rec.v, &rec.p).x;
+        let opacity = match self.mode {
+            AlphaMode::Stochastic => alpha.clamp(0.0, 1.0),
+            AlphaMode::Threshold(cutoff) => if alpha >= cutoff { 1.0 } else { 0.0 },
+        };
+        opacity * self.material.opacity(r_in, rec)
+    }
+}
+
+// Detail added to the shading normal from a texture.
+#[derive(Clone)]
+pub enum Perturbation {
+    // Tangent space normal map, with RGB in [0, 1] encoding XYZ in [-1, 1]
+    // along (tangent, bitangent, normal).
+    NormalMap(Arc<dyn Texture>),
+    // Grayscale height field, read from the first channel and multiplied by
+    // `scale`, in the same units as the surface positions per unit of uv.
+    Bump { height: Arc<dyn Texture>, scale: f64 }
+}
+
+// Step in uv used to difference bump maps.
+const BUMP_DELTA: f64 = 1e-3;
+
+// Keeps the perturbed normal at least this far (in cosine) in front of the
+// viewer.
+const MIN_SHADING_COSINE: f64 = 0.01;
+
+// Wraps a material, shading it with a perturbed normal.
+pub struct NormalMapped {
+    pub material: Arc<dyn Material>,
+    pub perturbation: Perturbation
+}
+
+impl NormalMapped {
+    pub fn new(material: Arc<dyn Material>, perturbation: Perturbation) -> Self {
+        Self{material, perturbation}
+    }
+
+    pub fn shading_normal(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> Vec3<f64> {
+        let n = rec.normal;
+        let (tangent, bitangent) = rec.tangent_frame();
+        let mut perturbed = match &self.perturbation {
+            Perturbation::NormalMap(map) => {
+                let m = 2.0 * map.value(rec.u, rec.v, &rec.p) - 1.0;
+                (m.x * tangent + m.y * bitangent + m.z * n).unit_vector()
+            }
+            Perturbation::Bump { height, scale } => {
+                // Fall back to unit speed along the frame when the shape has
+                // no derivatives.
+                let (dpdu, dpdv) = if rec.dpdu.near_zero() { (tangent, bitangent) } else { (rec.dpdu, rec.dpdv) };
+                let h = height.value(rec.u, rec.v, &rec.p).x;
+                let h_u = height.value(rec.u + BUMP_DELTA, rec.v, &(rec.p + BUMP_DELTA * dpdu)).x;
+                let h_v = height.value(rec.u, rec.v + BUMP_DELTA, &(rec.p + BUMP_DELTA * dpdv)).x;
+                let displaced_u = dpdu + (scale * (h_u - h) / BUMP_DELTA) * n;
+                let displaced_v = dpdv + (scale * (h_v - h) / BUMP_DELTA) * n;
+                let normal = displaced_u.cross(displaced_v).unit_vector();
+                if normal.dot(n) < 0.0 { -normal } else { normal }
+            }
         };
 
-        *scattered = Ray::new(rec.p, direction);
+        // A normal facing away from the viewer would shade the surface as if
+        // seen from behind. Bend it towards the viewer just enough instead.
+        let wo = -r_in.direction.unit_vector();
+        let cos_o = perturbed.dot(wo);
+        if cos_o < MIN_SHADING_COSINE {
+            perturbed = (perturbed + (MIN_SHADING_COSINE - cos_o) * wo).unit_vector();
+        }
+        perturbed
+    }
+}
+
+impl NormalMapped {
+    // The hit as seen by the wrapped material.
+    fn shading(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> HitRecord<f64> {
+        let mut shading = rec.clone();
+        shading.normal = self.shading_normal(r_in, rec);
+        shading
+    }
+}
+
+// Directions on opposite sides of the shading and geometric surfaces would
+// leak light through the object, so they are absorbed.
+fn leaks(direction: Vec3<f64>, shading: &HitRecord<f64>, rec: &HitRecord<f64>) -> bool {
+    (direction.dot(shading.normal) > 0.0) != (direction.dot(rec.geometric_normal) > 0.0)
+}
+
+impl Material for NormalMapped {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+        self.scatter_lobe(r_in, rec, attenuation, scattered).is_some()
+    }
+
+    fn scatter_lobe(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> Option<Lobe> {
+        let shading = self.shading(r_in, rec);
+        let lobe = self.material.scatter_lobe(r_in, &shading, attenuation, scattered)?;
+        if leaks(scattered.direction, &shading, rec) { None } else { Some(lobe) }
+    }
+
+    fn name(&self) -> &'static str {
+        self.material.name()
+    }
+
+    fn is_specular(&self) -> bool {
+        self.material.is_specular()
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: &Point3<f64>) -> Color<f64> {
+        self.material.emitted(u, v, p)
+    }
+
+    fn opacity(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> f64 {
+        self.material.opacity(r_in, rec)
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> f64 {
+        let shading = self.shading(r_in, rec);
+        if leaks(scattered.direction, &shading, rec) { 0.0 } else { self.material.scattering_pdf(r_in, &shading, scattered) }
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        let shading = self.shading(r_in, rec);
+        if leaks(scattered.direction, &shading, rec) { Color::zero() } else { self.material.eval(r_in, &shading, scattered) }
+    }
+}
+
+pub struct DiffuseLight {
+    pub emit: Color<f64>
+}
+
+impl DiffuseLight {
+    pub fn new(c: &Color<f64>) -> Self {
+        Self{emit: *c}
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _r_in: &Ray<f64>, _rec: &HitRecord<f64>, _attenuation: &mut Color<f64>, _scattered: &mut Ray<f64>) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "diffuse_light"
+    }
+
+    fn emitted(&self, _u: f64, _v: f64, _p: &Point3<f64>) -> Color<f64> {
+        self.emit
+    }
+}
+
+// Phase function scattering equally in every direction.
+pub struct Isotropic {
+    pub albedo: Color<f64>
+}
+
+impl Isotropic {
+    pub fn new(a: &Color<f64>) -> Self {
+        Self{albedo: *a}
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+        *scattered = Ray::with_time(rec.p, random_unit_vector(), r_in.time);
+        *attenuation = self.albedo;
         true
     }
+
+    fn name(&self) -> &'static str {
+        "isotropic"
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray<f64>, _rec: &HitRecord<f64>, _scattered: &Ray<f64>) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        self.scattering_pdf(r_in, rec, scattered) * self.albedo
+    }
+}
+
+// Henyey-Greenstein phase function. g in (-1, 1) is the mean cosine between
+// the incoming and scattered directions: positive scatters forward, negative
+// backward, zero is isotropic.
+pub struct HenyeyGreenstein {
+    pub albedo: Color<f64>,
+    pub g: f64
+}
+
+impl HenyeyGreenstein {
+    pub fn new(a: &Color<f64>, g: f64) -> Self {
+        Self{albedo: *a, g}
+    }
+
+    // Scattered direction for an incoming unit direction.
+    pub fn sample(&self, direction: Vec3<f64>) -> Vec3<f64> {
+        let cos_theta = self.cos_theta(rand_double());
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand_double();
+
+        let (tangent, bitangent) = orthonormal_basis(direction);
+        sin_theta * phi.cos() * tangent + sin_theta * phi.sin() * bitangent + cos_theta * direction
+    }
+
+    // Cosine of the scattering angle by inverting the phase function's
+    // cumulative distribution at `xi` in [0, 1).
+    fn cos_theta(&self, xi: f64) -> f64 {
+        let g = self.g;
+        if g.abs() < 1e-3 {
+            1.0 - 2.0 * xi
+        } else {
+            let sqr_term = (1.0 - g * g) / (1.0 + g - 2.0 * g * xi);
+            (1.0 + g * g - sqr_term * sqr_term) / (2.0 * g)
+        }
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+        *scattered = Ray::with_time(rec.p, self.sample(r_in.direction.unit_vector()), r_in.time);
+        *attenuation = self.albedo;
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "henyey_greenstein"
+    }
+
+    fn scattering_pdf(&self, r_in: &Ray<f64>, _rec: &HitRecord<f64>, scattered: &Ray<f64>) -> f64 {
+        let g = self.g;
+        let cos_theta = r_in.direction.unit_vector().dot(scattered.direction.unit_vector());
+        (1.0 - g * g) / (4.0 * PI * (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5))
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        self.scattering_pdf(r_in, rec, scattered) * self.albedo
+    }
+}
+
+// Phase function for hot media such as fire. Scatters isotropically and
+// glows with black body color, looking up the temperature in Kelvin at the
+// scattering point as `temperature_scale` times the `temperature` field.
+// Brightness follows the Stefan-Boltzmann T^4 law relative to
+// `reference_temperature`.
+pub struct EmissiveVolume {
+    pub albedo: Color<f64>,
+    pub temperature: Arc<dyn VolumeField>,
+    pub temperature_scale: f64,
+    pub intensity: f64,
+    pub reference_temperature: f64
+}
+
+impl EmissiveVolume {
+    pub fn new(a: &Color<f64>, temperature: Arc<dyn VolumeField>, temperature_scale: f64, intensity: f64, reference_temperature: f64) -> Self {
+        assert!(reference_temperature > 0.0, "reference temperature must be positive");
+        Self{albedo: *a, temperature, temperature_scale, intensity, reference_temperature}
+    }
+}
+
+impl Material for EmissiveVolume {
+    fn scatter(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, attenuation: &mut Color<f64>, scattered: &mut Ray<f64>) -> bool {
+        *scattered = Ray::with_time(rec.p, random_unit_vector(), r_in.time);
+        *attenuation = self.albedo;
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "emissive_volume"
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray<f64>, _rec: &HitRecord<f64>, _scattered: &Ray<f64>) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn eval(&self, r_in: &Ray<f64>, rec: &HitRecord<f64>, scattered: &Ray<f64>) -> Color<f64> {
+        self.scattering_pdf(r_in, rec, scattered) * self.albedo
+    }
+
+    fn emitted(&self, _u: f64, _v: f64, p: &Point3<f64>) -> Color<f64> {
+        let temperature = self.temperature.value(p) * self.temperature_scale;
+        self.intensity * (temperature / self.reference_temperature).powi(4) * blackbody_color(temperature)
+    }
 }
 
 fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
@@ -96,3 +837,216 @@ fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
     r0 + (1.0 - r0) * (1.0  - cosine).powi(5)
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::texture::SolidColor;
+
+    // Hit at the origin on a surface facing +y, seen from outside.
+    fn hit_facing_up() -> HitRecord<f64> {
+        let mut rec = HitRecord::zero();
+        rec.normal = Vec3::new(0.0, 1.0, 0.0);
+        rec.geometric_normal = rec.normal;
+        rec.front_face = true;
+        rec
+    }
+
+    // Ray towards the origin from above, at `cos_theta` to the +y normal.
+    fn arriving(cos_theta: f64) -> Ray<f64> {
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        Ray::new(Point3::new(-sin_theta, cos_theta, 0.0), Vec3::new(sin_theta, -cos_theta, 0.0))
+    }
+
+    // Integral of `eval` over the sphere by the midpoint rule in cos(theta)
+    // and phi, which is exact for Lambertian lobes: the fraction of the
+    // light arriving along `r_in` that the diffuse lobes send back out.
+    fn diffuse_albedo(material: &dyn Material, r_in: &Ray<f64>, rec: &HitRecord<f64>) -> Color<f64> {
+        let n = 64;
+        let mut total = Color::zero();
+        for i in 0..n {
+            let z = -1.0 + 2.0 * (i as f64 + 0.5) / n as f64;
+            let r = (1.0 - z * z).sqrt();
+            for j in 0..n {
+                let phi = 2.0 * PI * (j as f64 + 0.5) / n as f64;
+                let scattered = Ray::new(rec.p, Vec3::new(r * phi.cos(), z, r * phi.sin()));
+                total += material.eval(r_in, rec, &scattered);
+            }
+        }
+        (4.0 * PI / (n * n) as f64) * total
+    }
+
+    #[test]
+    fn glass_bends_blue_more_than_red() {
+        let glass = Dielectric::new(1.5).with_dispersion(Dispersion::BK7);
+        assert!((glass.index_of_refraction - 1.5168).abs() < 1e-3);
+        assert!(glass.ior(450.0) > glass.ior(650.0));
+        assert_eq!(glass.ior(0.0), glass.index_of_refraction);
+
+        let cauchy = Dispersion::Cauchy { a: 1.5046, b: 0.0042 };
+        assert!((cauchy.ior(1000.0) - 1.5088).abs() < 1e-9);
+    }
+
+    #[test]
+    fn coating_never_adds_energy() {
+        let white = Arc::new(Lambertian::new(&Color::new(1.0, 1.0, 1.0)));
+        let coated = Coated::new(white.clone(), 1.5, 0.0).with_tint(Color::new(1.0, 0.5, 1.0));
+        let rec = hit_facing_up();
+        assert!((diffuse_albedo(white.as_ref(), &arriving(1.0), &rec) - Color::new(1.0, 1.0, 1.0)).length() < 1e-9);
+
+        // What the coating reflects plus what gets through to the base and
+        // back out again is at most what arrived.
+        for cos_in in [1.0, 0.5, 0.1] {
+            let mirror = reflectance(cos_in, 1.0 / 1.5);
+            let total = mirror + diffuse_albedo(&coated, &arriving(cos_in), &rec).x;
+            assert!(total <= 1.0 && total > 0.85, "cos {}: {}", cos_in, total);
+        }
+        let tinted = diffuse_albedo(&coated, &arriving(1.0), &rec);
+        assert!(tinted.y < 0.5 * tinted.x);
+    }
+
+    #[test]
+    fn mix_follows_its_weight() {
+        let dark = Arc::new(Lambertian::new(&Color::zero()));
+        let light = Arc::new(Lambertian::new(&Color::new(1.0, 1.0, 1.0)));
+        let inner = Arc::new(Mix::new(dark.clone(), light.clone(), MixWeight::Constant(0.5)));
+        let mix = Mix::new(dark, inner, MixWeight::Constant(0.5));
+        let rec = hit_facing_up();
+
+        let albedo = diffuse_albedo(&mix, &arriving(1.0), &rec);
+        assert!((albedo - Color::new(0.25, 0.25, 0.25)).length() < 1e-9);
+        let up = Ray::new(rec.p, rec.normal);
+        assert_eq!(mix.scattering_pdf(&arriving(1.0), &rec, &up), light.scattering_pdf(&arriving(1.0), &rec, &up));
+
+        // Fresnel weights favour the second material at grazing angles.
+        let fresnel = MixWeight::Fresnel(1.5);
+        assert!(fresnel.value(0.05, 0.0, 0.0, &rec.p) > 10.0 * fresnel.value(1.0, 0.0, 0.0, &rec.p));
+    }
+
+    #[test]
+    fn wrappers_keep_nested_cutouts() {
+        let gray: Arc<dyn Material> = Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)));
+        let hole: Arc<dyn Material> = Arc::new(Cutout::new(gray.clone(), Arc::new(SolidColor::new(&Color::zero())), AlphaMode::Threshold(0.5)));
+        let rec = hit_facing_up();
+        let (head_on, grazing) = (arriving(1.0), arriving(0.05));
+
+        assert!(Coated::new(hole.clone(), 1.5, 0.0).is_transparent(&head_on, &rec));
+        assert!(!Coated::new(gray.clone(), 1.5, 0.0).is_transparent(&head_on, &rec));
+        assert!(Mix::new(gray.clone(), hole.clone(), MixWeight::Constant(1.0)).is_transparent(&head_on, &rec));
+        assert!(!Mix::new(gray.clone(), hole.clone(), MixWeight::Constant(0.0)).is_transparent(&head_on, &rec));
+
+        // A cutout under a half mix is there half the time, and a kept hit
+        // is always shaded with the other child.
+        let dark: Arc<dyn Material> = Arc::new(Lambertian::new(&Color::zero()));
+        let mix = Mix::new(dark, hole.clone(), MixWeight::Constant(0.5));
+        assert_eq!(mix.opacity(&head_on, &rec), 0.5);
+        assert_eq!(mix.b_weight(&head_on, &rec), 0.0);
+
+        // View dependent weights see the actual angle of incidence.
+        let rim = Mix::new(gray, hole, MixWeight::Fresnel(1.5));
+        assert!((rim.opacity(&head_on, &rec) - 0.96).abs() < 1e-9);
+        assert!(rim.opacity(&grazing, &rec) < 0.5);
+    }
+
+    #[test]
+    fn normal_maps_tilt_within_the_tangent_frame() {
+        let gray = Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5)));
+        let map = |c: Color<f64>| Perturbation::NormalMap(Arc::new(SolidColor::new(&c)));
+        let mut rec = HitRecord::zero();
+        rec.normal = Vec3::new(0.0, 0.0, 1.0);
+        rec.geometric_normal = rec.normal;
+        rec.dpdu = Vec3::new(2.0, 0.0, 0.0);
+        rec.dpdv = Vec3::new(0.0, 3.0, 0.0);
+        let head_on = Ray::new(Point3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let flat = NormalMapped::new(gray.clone(), map(Color::new(0.5, 0.5, 1.0)));
+        assert!((flat.shading_normal(&head_on, &rec) - rec.normal).length() < 1e-9);
+
+        let tilted = NormalMapped::new(gray.clone(), map(Color::new(1.0, 0.5, 1.0)));
+        let h = 0.5f64.sqrt();
+        assert!((tilted.shading_normal(&head_on, &rec) - Vec3::new(h, 0.0, h)).length() < 1e-9);
+
+        // Seen at a grazing angle from the side the normal tilts away from,
+        // the normal is bent back to face the viewer.
+        let grazing = Ray::new(Point3::new(-1.0, 0.0, 0.1), Vec3::new(1.0, 0.0, -0.1));
+        let n = tilted.shading_normal(&grazing, &rec);
+        assert!(n.dot(-grazing.direction.unit_vector()) > 0.0);
+
+        // A constant height field leaves the normal alone.
+        let bump = NormalMapped::new(gray, Perturbation::Bump { height: Arc::new(SolidColor::new(&Color::new(0.3, 0.3, 0.3))), scale: 1.0 });
+        assert!((bump.shading_normal(&head_on, &rec) - rec.normal).length() < 1e-9);
+    }
+
+    #[test]
+    fn dielectric_absorbs_along_interior_path() {
+        let glass = Dielectric::with_transmittance(1.5, &Color::new(0.5, 1.0, 0.25), 1.0);
+        let r = Ray::new(Point3::zero(), Vec3::new(2.0, 0.0, 0.0));
+        let mut rec = HitRecord::zero();
+        rec.t = 1.0;
+        rec.normal = Vec3::new(-1.0, 0.0, 0.0);
+        rec.front_face = false;
+
+        // Two units inside: each channel is its transmittance squared.
+        let mut attenuation = Color::zero();
+        let mut scattered = r;
+        assert!(glass.scatter(&r, &rec, &mut attenuation, &mut scattered));
+        assert!((attenuation - Color::new(0.25, 1.0, 0.0625)).length() < 1e-9);
+
+        // Entering from outside is not attenuated.
+        rec.front_face = true;
+        glass.scatter(&r, &rec, &mut attenuation, &mut scattered);
+        assert!((attenuation - Color::new(1.0, 1.0, 1.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn filmed_glass_never_adds_energy() {
+        // Reflection and transmission share the light: picking one with
+        // probability p and weighting it by its share over p leaves each
+        // channel's expected attenuation at one.
+        let film = ThinFilm::new(400.0, 1.33);
+        for cos_theta in [1.0, 0.7, 0.2] {
+            for front_face in [true, false] {
+                let (p, reflected, refracted) = film_lobes(&film, cos_theta, front_face, 1.5, 0.0);
+                let expected = p * reflected + (1.0 - p) * refracted;
+                assert!((expected - Color::new(1.0, 1.0, 1.0)).length() < 1e-12, "got {:?}", expected);
+                assert!(reflected.x >= 0.0 && refracted.x >= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn henyey_greenstein_mean_cosine_is_g() {
+        for g in [-0.7, 0.0, 0.3, 0.9] {
+            let phase = HenyeyGreenstein::new(&Color::new(1.0, 1.0, 1.0), g);
+
+            // Stratified samples of the inverted distribution.
+            let n = 10_000;
+            let mean: f64 = (0..n).map(|i| phase.cos_theta((i as f64 + 0.5) / n as f64)).sum::<f64>() / n as f64;
+            assert!((mean - g).abs() < 1e-3, "g = {}, mean cosine = {}", g, mean);
+
+            // The density integrates to one, with the same mean cosine.
+            let incoming = arriving(1.0);
+            let (mut total, mut cosine) = (0.0, 0.0);
+            for i in 0..n {
+                let z = -1.0 + 2.0 * (i as f64 + 0.5) / n as f64;
+                let scattered = Ray::new(Point3::zero(), Vec3::new((1.0 - z * z).sqrt(), -z, 0.0));
+                let pdf = phase.scattering_pdf(&incoming, &hit_facing_up(), &scattered);
+                total += 4.0 * PI / n as f64 * pdf;
+                cosine += 4.0 * PI / n as f64 * pdf * z;
+            }
+            assert!((total - 1.0).abs() < 1e-3 && (cosine - g).abs() < 1e-3, "g = {}: {} {}", g, total, cosine);
+        }
+    }
+
+    #[test]
+    fn emissive_volume_reads_temperature_at_the_hit() {
+        use crate::aabb::Aabb;
+        use crate::volume::VoxelGrid;
+
+        let field = Arc::new(VoxelGrid::new(2, 1, 1, vec![1.0, 2.0], Aabb::new(Point3::zero(), Point3::new(2.0, 1.0, 1.0))));
+        let fire = EmissiveVolume::new(&Color::new(0.5, 0.5, 0.5), field, 1000.0, 3.0, 1000.0);
+        let cool = fire.emitted(0.0, 0.0, &Point3::new(0.5, 0.5, 0.5));
+        let hot = fire.emitted(0.0, 0.0, &Point3::new(1.5, 0.5, 0.5));
+        assert!((cool - 3.0 * blackbody_color(1000.0)).length() < 1e-9);
+        assert!((hot - 48.0 * blackbody_color(2000.0)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::aabb::*;
+use crate::constants::PI;
+use crate::transform::*;
+use crate::utilities::degrees_to_radians;
+use crate::vec3::*;
+
+// Unit quaternion, used to interpolate rotations between keyframes.
+#[derive(Debug, Clone, Copy)]
+pub struct Quaternion {
+    pub w: f64,
+    pub v: Vec3<f64>
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        Self { w: 1.0, v: Vec3::zero() }
+    }
+
+    pub fn from_axis_angle(axis: Vec3<f64>, degrees: f64) -> Self {
+        let half = degrees_to_radians(degrees) / 2.0;
+        Self { w: half.cos(), v: half.sin() * axis.unit_vector() }
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.w * other.w + self.v.dot(other.v)
+    }
+
+    // Spherical linear interpolation along the shorter arc.
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        let mut cos_theta = self.dot(other);
+        let mut other = *other;
+        if cos_theta < 0.0 {
+            cos_theta = -cos_theta;
+            other = Self { w: -other.w, v: -other.v };
+        }
+
+        let (a, b) = if cos_theta > 0.9995 {
+            // Nearly parallel: fall back to linear interpolation.
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+        };
+
+        let w = a * self.w + b * other.w;
+        let v = a * self.v + b * other.v;
+        let norm = (w * w + v.length_squared()).sqrt();
+        Self { w: w / norm, v: v / norm }
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        let sin_half = self.v.length();
+        if sin_half < 1e-12 {
+            return Transform::identity();
+        }
+        let degrees = 2.0 * sin_half.atan2(self.w) * 180.0 / PI;
+        Transform::rotate(self.v / sin_half, degrees)
+    }
+}
+
+// Object pose at a point in time: scale, then rotate, then translate.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vec3<f64>,
+    pub rotation: Quaternion,
+    pub scale: Vec3<f64>
+}
+
+impl Keyframe {
+    pub fn new(time: f64, translation: Vec3<f64>, rotation: Quaternion, scale: Vec3<f64>) -> Self {
+        Self { time, translation, rotation, scale }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::translate(self.translation) * self.rotation.to_transform() * Transform::scale(self.scale)
+    }
+}
+
+// Static transforms are the common case, so keep them inline rather than boxed.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum AnimatedTransform {
+    Static(Transform),
+    // Keyframes sorted by time; poses are held constant outside their range.
+    Keyframed(Vec<Keyframe>)
+}
+
+// Number of poses sampled across the shutter interval when bounding motion.
+const BOUNDING_STEPS: usize = 64;
+
+impl AnimatedTransform {
+    pub fn keyframed(mut keyframes: Vec<Keyframe>) -> Self {
+        assert!(!keyframes.is_empty(), "an animated transform needs at least one keyframe");
+        assert!(keyframes.iter().all(|k| k.time.is_finite()), "keyframe times must be finite");
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        AnimatedTransform::Keyframed(keyframes)
+    }
+
+    pub fn at(&self, time: f64) -> Transform {
+        match self {
+            AnimatedTransform::Static(t) => *t,
+            AnimatedTransform::Keyframed(keys) => {
+                let next = keys.partition_point(|k| k.time <= time);
+                if next == 0 {
+                    return keys[0].transform();
+                }
+                if next == keys.len() {
+                    return keys[keys.len() - 1].transform();
+                }
+
+                let (k0, k1) = (&keys[next - 1], &keys[next]);
+                let s = (time - k0.time) / (k1.time - k0.time);
+                let translation = (1.0 - s) * k0.translation + s * k1.translation;
+                let scale = (1.0 - s) * k0.scale + s * k1.scale;
+                let rotation = k0.rotation.slerp(&k1.rotation, s);
+                Transform::translate(translation) * rotation.to_transform() * Transform::scale(scale)
+            }
+        }
+    }
+
+    // Box around `b` over every pose in [time0, time1], found by sampling the
+    // interval and every keyframe inside it, then growing by how far a point
+    // can stray from the straight line between samples.
+    pub fn bounding_box(&self, b: &Aabb, time0: f64, time1: f64) -> Aabb {
+        match self {
+            AnimatedTransform::Static(t) => t.bounding_box(b),
+            AnimatedTransform::Keyframed(keys) => {
+                let mut result = self.at(time0).bounding_box(b);
+                let samples = (1..=BOUNDING_STEPS).map(|i| time0 + (time1 - time0) * i as f64 / BOUNDING_STEPS as f64);
+                let key_times = keys.iter().map(|k| k.time).filter(|t| *t > time0 && *t < time1);
+                for time in samples.chain(key_times) {
+                    result = surrounding_box(&result, &self.at(time).bounding_box(b));
+                }
+                let e = chord_error(keys, b, time0, time1);
+                let e = Vec3::new(e, e, e);
+                Aabb::new(result.minimum - e, result.maximum + e)
+            }
+        }
+    }
+}
+
+// Bound on the distance between a point's path and the chord joining two
+// consecutive samples, h^2 / 8 * max |p''| for a step of h. Within a segment
+// p(t) = T(t) + R(t) S(t) x with T and S linear and R turning at a constant
+// rate w, so |p''| <= w^2 |S x| + 2 w |S' x|.
+fn chord_error(keys: &[Keyframe], b: &Aabb, time0: f64, time1: f64) -> f64 {
+    let h = (time1 - time0).abs() / BOUNDING_STEPS as f64;
+    let mut error: f64 = 0.0;
+    for pair in keys.windows(2) {
+        let (k0, k1) = (&pair[0], &pair[1]);
+        let duration = k1.time - k0.time;
+        if duration <= 0.0 || k1.time <= time0.min(time1) || k0.time >= time0.max(time1) {
+            continue;
+        }
+        let w = 2.0 * k0.rotation.dot(&k1.rotation).abs().min(1.0).acos() / duration;
+
+        // Largest |S x| and |S' x| over the corners of the box.
+        let (mut reach, mut growth): (f64, f64) = (0.0, 0.0);
+        for corner in 0..8 {
+            let x = Vec3::new(
+                if corner & 1 == 0 { b.minimum.x } else { b.maximum.x },
+                if corner & 2 == 0 { b.minimum.y } else { b.maximum.y },
+                if corner & 4 == 0 { b.minimum.z } else { b.maximum.z },
+            );
+            let s = Vec3::new(k0.scale.x.abs().max(k1.scale.x.abs()), k0.scale.y.abs().max(k1.scale.y.abs()), k0.scale.z.abs().max(k1.scale.z.abs()));
+            reach = reach.max((s * x).length());
+            growth = growth.max(((k1.scale - k0.scale) * x).length() / duration);
+        }
+        error = error.max(h * h / 8.0 * (w * w * reach + 2.0 * w * growth));
+    }
+    error
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn interpolates_between_keyframes() {
+        let motion = AnimatedTransform::keyframed(vec![
+            Keyframe::new(1.0, Vec3::new(2.0, 0.0, 0.0), Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 90.0), Vec3::new(1.0, 1.0, 1.0)),
+            Keyframe::new(0.0, Vec3::zero(), Quaternion::identity(), Vec3::new(1.0, 1.0, 1.0)),
+        ]);
+
+        // Halfway: moved by 1 and turned 45 degrees about y.
+        let p = motion.at(0.5).point(Point3::new(1.0, 0.0, 0.0));
+        let h = 0.5f64.sqrt();
+        assert!((p - Point3::new(1.0 + h, 0.0, -h)).length() < 1e-9);
+
+        // Clamped outside the keyframe range.
+        let p = motion.at(3.0).point(Point3::zero());
+        assert!((p - Point3::new(2.0, 0.0, 0.0)).length() < 1e-9);
+
+        let b = motion.bounding_box(&Aabb::new(Point3::zero(), Point3::zero()), 0.0, 1.0);
+        assert!(b.minimum.x <= 0.0 && b.maximum.x >= 2.0);
+    }
+
+    #[test]
+    fn bounds_cover_poses_between_samples() {
+        // A point swinging through 179 degrees about y reaches z = -1 at 90
+        // degrees, which falls between bounding samples.
+        let motion = AnimatedTransform::keyframed(vec![
+            Keyframe::new(0.0, Vec3::zero(), Quaternion::identity(), Vec3::new(1.0, 1.0, 1.0)),
+            Keyframe::new(1.0, Vec3::zero(), Quaternion::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 179.0), Vec3::new(1.0, 1.0, 1.0)),
+        ]);
+        let point = Aabb::new(Point3::new(1.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0));
+        let b = motion.bounding_box(&point, 0.0, 1.0);
+        assert!(b.minimum.z <= -1.0, "got {:?}", b);
+        for i in 0..=1000 {
+            let p = motion.at(i as f64 / 1000.0).point(Point3::new(1.0, 0.0, 0.0));
+            assert!(p.z >= b.minimum.z && p.x >= b.minimum.x && p.x <= b.maximum.x);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "finite")]
+    fn rejects_nan_keyframe_times() {
+        AnimatedTransform::keyframed(vec![
+            Keyframe::new(0.0, Vec3::zero(), Quaternion::identity(), Vec3::new(1.0, 1.0, 1.0)),
+            Keyframe::new(f64::NAN, Vec3::zero(), Quaternion::identity(), Vec3::new(1.0, 1.0, 1.0)),
+        ]);
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::hittable::*;
+use crate::ray::Ray;
+use crate::sphere::hit_sphere;
+use crate::vec3::*;
+use crate::material::*;
+
+// Sphere moving linearly from center0 at time0 to center1 at time1.
+#[derive(Clone)]
+pub struct MovingSphere {
+    pub center0: Point3<f64>,
+    pub center1: Point3<f64>,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub mat_ptr: Arc<dyn Material>
+}
+
+impl MovingSphere {
+    pub fn new(center0: Point3<f64>, center1: Point3<f64>, time0: f64, time1: f64, radius: f64, mat_ptr: Arc<dyn Material>) -> Self {
+        Self { center0, center1, time0, time1, radius, mat_ptr }
+    }
+
+    pub fn center(&self, time: f64) -> Point3<f64> {
+        // A sphere given no time to move stays at its first center.
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        self.center0 + ((time - self.time0) / (self.time1 - self.time0)) * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        hit_sphere(self.center(r.time), self.radius, &self.mat_ptr, r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        let box0 = Aabb::new(self.center(time0) - r, self.center(time0) + r);
+        let box1 = Aabb::new(self.center(time1) - r, self.center(time1) + r);
+        *output_box = surrounding_box(&box0, &box1);
+        true
+    }
+}
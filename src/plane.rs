@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::hittable::*;
+use crate::vec3::*;
+use crate::material::*;
+
+// Infinite plane through `point`. UVs are planar coordinates in units of
+// `uv_scale`, wrapped into [0, 1) so textures tile across the plane.
+#[derive(Clone)]
+pub struct Plane {
+    pub point: Point3<f64>,
+    pub normal: Vec3<f64>,
+    pub uv_scale: f64,
+    pub mat_ptr: Arc<dyn Material>
+}
+
+impl Plane {
+    pub fn new(point: Point3<f64>, normal: Vec3<f64>, mat_ptr: Arc<dyn Material>) -> Self {
+        Self { point, normal: normal.unit_vector(), uv_scale: 1.0, mat_ptr }
+    }
+
+    // Two unit vectors spanning the plane, used for UVs.
+    fn tangents(&self) -> (Vec3<f64>, Vec3<f64>) {
+        orthonormal_basis(self.normal)
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: crate::ray::Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let denom = self.normal.dot(r.direction);
+
+        // No hit if the ray is parallel to the plane.
+        if denom.abs() < 1e-8 { return false; }
+
+        let t = (self.point - r.origin).dot(self.normal) / denom;
+        if t < t_min || t_max < t { return false; }
+
+        rec.t = t;
+        rec.p = r.at(t);
+        let (tangent, bitangent) = self.tangents();
+        let d = rec.p - self.point;
+        rec.u = (d.dot(tangent) / self.uv_scale).rem_euclid(1.0);
+        rec.v = (d.dot(bitangent) / self.uv_scale).rem_euclid(1.0);
+        rec.set_face_normal(&r, &self.normal);
+        rec.dpdu = self.uv_scale * tangent;
+        rec.dpdv = self.uv_scale * bitangent;
+        rec.mat_ptr = self.mat_ptr.clone();
+
+        true
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, _output_box: &mut Aabb) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Plane;
+    use crate::aabb::Aabb;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn hits_both_sides_and_tiles_uvs() {
+        let mut plane = Plane::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 2.0, 0.0), Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        plane.uv_scale = 2.0;
+        let mut rec = HitRecord::zero();
+
+        // Straight down from above lands on the front face.
+        let r = Ray::new(Point3::new(3.0, 5.0, -1.0), Vec3::new(0.0, -2.0, 0.0));
+        assert!(plane.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.0).abs() < 1e-12);
+        assert!((rec.p - Point3::new(3.0, 1.0, -1.0)).length() < 1e-12);
+        assert!((rec.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-12);
+        assert!(rec.front_face);
+
+        // UVs are the planar coordinates over uv_scale, wrapped into [0, 1).
+        let (tangent, bitangent) = orthonormal_basis(Vec3::new(0.0, 1.0, 0.0));
+        let d = rec.p - plane.point;
+        assert!((rec.u - (d.dot(tangent) / 2.0).rem_euclid(1.0)).abs() < 1e-12);
+        assert!((rec.v - (d.dot(bitangent) / 2.0).rem_euclid(1.0)).abs() < 1e-12);
+        assert!((0.0..1.0).contains(&rec.u) && (0.0..1.0).contains(&rec.v));
+        assert!((rec.dpdu - 2.0 * tangent).length() < 1e-12);
+
+        // From below the normal faces back at the ray.
+        let r = Ray::new(Point3::new(0.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        assert!(plane.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.normal - Vec3::new(0.0, -1.0, 0.0)).length() < 1e-12);
+        assert!(!rec.front_face);
+
+        // Parallel rays, hits behind the origin and hits past t_max miss.
+        assert!(!plane.hit(Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut rec));
+        assert!(!plane.hit(Ray::new(Point3::zero(), Vec3::new(0.0, -1.0, 0.0)), 0.001, f64::INFINITY, &mut rec));
+        assert!(!plane.hit(Ray::new(Point3::zero(), Vec3::new(0.0, 1.0, 0.0)), 0.001, 0.5, &mut rec));
+
+        // An infinite plane has no bounding box.
+        assert!(!plane.bounding_box(0.0, 1.0, &mut Aabb::zero()));
+    }
+}
@@ -0,0 +1,161 @@
+// Real roots of low-order polynomials, after Jochen Schwarze's solvers in
+// Graphics Gems I. Coefficients are given highest order first; roots are
+// returned in no particular order.
+
+const EPSILON: f64 = 1e-12;
+
+#[inline]
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+// a x^2 + b x + c = 0
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return if is_zero(b) { Vec::new() } else { vec![-c / b] };
+    }
+
+    // Normal form: x^2 + p x + q = 0
+    let p = b / (2.0 * a);
+    let q = c / a;
+    let d = p * p - q;
+
+    if is_zero(d) {
+        vec![-p]
+    } else if d < 0.0 {
+        Vec::new()
+    } else {
+        let sqrt_d = d.sqrt();
+        vec![sqrt_d - p, -sqrt_d - p]
+    }
+}
+
+// a x^3 + b x^2 + c x + d = 0
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return solve_quadratic(b, c, d);
+    }
+
+    // Normal form: x^3 + A x^2 + B x + C = 0
+    let (ca, cb, cc) = (b / a, c / a, d / a);
+
+    // Substitute x = y - A/3 to eliminate the quadric term: y^3 + 3p y + 2q = 0
+    let sq_a = ca * ca;
+    let p = (-sq_a / 3.0 + cb) / 3.0;
+    let q = (2.0 / 27.0 * ca * sq_a - ca * cb / 3.0 + cc) / 2.0;
+
+    let cb_p = p * p * p;
+    let disc = q * q + cb_p;
+
+    let mut roots = if is_zero(disc) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if disc < 0.0 {
+        // Three real roots.
+        let phi = (-q / (-cb_p).sqrt()).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + std::f64::consts::PI / 3.0).cos(),
+            -t * (phi - std::f64::consts::PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_d = disc.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+
+    let sub = ca / 3.0;
+    for r in roots.iter_mut() {
+        *r -= sub;
+    }
+    roots
+}
+
+// a x^4 + b x^3 + c x^2 + d x + e = 0
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return solve_cubic(b, c, d, e);
+    }
+
+    // Normal form: x^4 + A x^3 + B x^2 + C x + D = 0
+    let (ca, cb, cc, cd) = (b / a, c / a, d / a, e / a);
+
+    // Substitute x = y - A/4 to eliminate the cubic term: y^4 + p y^2 + q y + r = 0
+    let sq_a = ca * ca;
+    let p = -3.0 / 8.0 * sq_a + cb;
+    let q = 1.0 / 8.0 * sq_a * ca - 1.0 / 2.0 * ca * cb + cc;
+    let r = -3.0 / 256.0 * sq_a * sq_a + 1.0 / 16.0 * sq_a * cb - 1.0 / 4.0 * ca * cc + cd;
+
+    let mut roots = if is_zero(r) {
+        // No absolute term: y (y^3 + p y + q) = 0
+        let mut roots = solve_cubic(1.0, 0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // Solve the resolvent cubic and take one real root to build two quadrics.
+        let z = solve_cubic(1.0, -0.5 * p, -r, 0.5 * r * p - 0.125 * q * q)[0];
+
+        let mut u = z * z - r;
+        let mut v = 2.0 * z - p;
+        u = if is_zero(u) { 0.0 } else if u > 0.0 { u.sqrt() } else { return Vec::new() };
+        v = if is_zero(v) { 0.0 } else if v > 0.0 { v.sqrt() } else { return Vec::new() };
+
+        let v = if q < 0.0 { -v } else { v };
+        let mut roots = solve_quadratic(1.0, v, z - u);
+        roots.extend(solve_quadratic(1.0, -v, z + u));
+        roots
+    };
+
+    let sub = ca / 4.0;
+    for root in roots.iter_mut() {
+        *root -= sub;
+        // Polish with Newton's method; the closed form loses precision badly.
+        for _ in 0..2 {
+            let x = *root;
+            let f = (((a * x + b) * x + c) * x + d) * x + e;
+            let df = ((4.0 * a * x + 3.0 * b) * x + 2.0 * c) * x + d;
+            if df.abs() > EPSILON {
+                *root = x - f / df;
+            }
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted(mut roots: Vec<f64>) -> Vec<f64> {
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        roots
+    }
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]) {
+        let roots = sorted(roots);
+        assert_eq!(roots.len(), expected.len(), "{:?}", roots);
+        for (r, e) in roots.iter().zip(expected) {
+            assert!((r - e).abs() < 1e-6, "{:?} vs {:?}", roots, expected);
+        }
+    }
+
+    #[test]
+    fn cubic() {
+        // (x - 1)(x - 2)(x + 3)
+        assert_roots(solve_cubic(1.0, 0.0, -7.0, 6.0), &[-3.0, 1.0, 2.0]);
+        assert_roots(solve_cubic(2.0, 0.0, 0.0, -16.0), &[2.0]);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0), &[1.0, 2.0, 3.0, 4.0]);
+        // (x^2 + 1)(x - 5)(x + 0.5)
+        assert_roots(solve_quartic(1.0, -4.5, -1.5, -4.5, -2.5), &[-0.5, 5.0]);
+        assert!(solve_quartic(1.0, 0.0, 2.0, 0.0, 1.0).is_empty());
+    }
+}
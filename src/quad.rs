@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::hittable::*;
+use crate::vec3::*;
+use crate::material::*;
+use crate::utilities::rand_double;
+
+// Parallelogram with corner `q` and edges `u` and `v`.
+#[derive(Clone)]
+pub struct Quad {
+    pub q: Point3<f64>,
+    pub u: Vec3<f64>,
+    pub v: Vec3<f64>,
+    pub mat_ptr: Arc<dyn Material>,
+    normal: Vec3<f64>,
+    d: f64,
+    w: Vec3<f64>
+}
+
+impl Quad {
+    pub fn new(q: Point3<f64>, u: Vec3<f64>, v: Vec3<f64>, mat_ptr: Arc<dyn Material>) -> Self {
+        let n = u.cross(v);
+        let normal = n.unit_vector();
+        let d = normal.dot(q);
+        let w = n / n.dot(n);
+        Self { q, u, v, mat_ptr, normal, d, w }
+    }
+
+    // Axis-aligned rectangles, facing +z, +x and +y respectively.
+    pub fn xy(x0: f64, x1: f64, y0: f64, y1: f64, k: f64, mat_ptr: Arc<dyn Material>) -> Self {
+        Self::new(Point3::new(x0, y0, k), Vec3::new(x1 - x0, 0.0, 0.0), Vec3::new(0.0, y1 - y0, 0.0), mat_ptr)
+    }
+
+    pub fn yz(y0: f64, y1: f64, z0: f64, z1: f64, k: f64, mat_ptr: Arc<dyn Material>) -> Self {
+        Self::new(Point3::new(k, y0, z0), Vec3::new(0.0, y1 - y0, 0.0), Vec3::new(0.0, 0.0, z1 - z0), mat_ptr)
+    }
+
+    pub fn xz(x0: f64, x1: f64, z0: f64, z1: f64, k: f64, mat_ptr: Arc<dyn Material>) -> Self {
+        Self::new(Point3::new(x0, k, z0), Vec3::new(0.0, 0.0, z1 - z0), Vec3::new(x1 - x0, 0.0, 0.0), mat_ptr)
+    }
+
+    pub fn normal(&self) -> Vec3<f64> {
+        self.normal
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: crate::ray::Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let denom = self.normal.dot(r.direction);
+
+        // No hit if the ray is parallel to the plane.
+        if denom.abs() < 1e-8 { return false; }
+
+        let t = (self.d - self.normal.dot(r.origin)) / denom;
+        if t < t_min || t_max < t { return false; }
+
+        // Express the hit point in the plane's (u, v) coordinates.
+        let intersection = r.at(t);
+        let planar_hitpt_vector = intersection - self.q;
+        let alpha = self.w.dot(planar_hitpt_vector.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar_hitpt_vector));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) { return false; }
+
+        rec.t = t;
+        rec.p = intersection;
+        rec.u = alpha;
+        rec.v = beta;
+        rec.set_face_normal(&r, &self.normal);
+        rec.dpdu = self.u;
+        rec.dpdv = self.v;
+        rec.mat_ptr = self.mat_ptr.clone();
+
+        true
+    }
+
+    fn area(&self) -> f64 {
+        self.u.cross(self.v).length()
+    }
+
+    fn random(&self, origin: &Point3<f64>) -> Vec3<f64> {
+        let p = self.q + rand_double() * self.u + rand_double() * self.v;
+        (p - *origin).unit_vector()
+    }
+
+    fn pdf_value(&self, origin: &Point3<f64>, direction: &Vec3<f64>) -> f64 {
+        area_pdf(self, origin, direction)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut Aabb) -> bool {
+        let diagonal1 = Aabb::from_points(self.q, self.q + self.u + self.v);
+        let diagonal2 = Aabb::from_points(self.q + self.u, self.q + self.v);
+        *output_box = surrounding_box(&diagonal1, &diagonal2).pad(1e-4);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Quad;
+    use crate::aabb::Aabb;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn hit_reports_uv_and_face() {
+        let quad = Quad::xz(-1.0, 1.0, -2.0, 2.0, 0.5, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        let mut rec = HitRecord::zero();
+
+        let down = Ray::new(Point3::new(0.5, 3.0, -1.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(quad.hit(down, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.5).abs() < 1e-9);
+        assert!((rec.u - 0.25).abs() < 1e-9 && (rec.v - 0.75).abs() < 1e-9);
+        assert!(rec.front_face);
+
+        let outside = Ray::new(Point3::new(1.5, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(!quad.hit(outside, 0.001, f64::INFINITY, &mut rec));
+
+        let mut bbox = Aabb::zero();
+        assert!(quad.bounding_box(0.0, 1.0, &mut bbox));
+        assert!(bbox.maximum.y > bbox.minimum.y);
+        assert!(bbox.hit(&down, 0.001, f64::INFINITY));
+    }
+}
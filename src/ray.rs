@@ -3,12 +3,19 @@ use crate::vec3::*;
 #[derive(Debug, Clone, Copy)]
 pub struct Ray<T> {
     pub origin: Point3<T>,
-    pub direction: Vec3<T>
+    pub direction: Vec3<T>,
+    pub time: T,
+    // Wavelength in nanometers carried by a spectral path, or zero for RGB.
+    pub wavelength: T
 }
 
 impl Ray<f64> {
     pub fn new(origin: Point3<f64>, direction: Vec3<f64>) -> Ray<f64> {
-        Ray { origin: origin, direction: direction }
+        Ray::with_time(origin, direction, 0.0)
+    }
+
+    pub fn with_time(origin: Point3<f64>, direction: Vec3<f64>, time: f64) -> Ray<f64> {
+        Ray { origin, direction, time, wavelength: 0.0 }
     }
 
     pub fn x_unit() -> Ray<f64> {
@@ -18,4 +25,4 @@ impl Ray<f64> {
     pub fn at(&self, t: f64) -> Point3<f64> {
         self.origin + t * self.direction
     }
-}
\ No newline at end of file
+}
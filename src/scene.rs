@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use crate::environment::*;
+use crate::hittable::Hittable;
+use crate::hittable_list::*;
+use crate::light::*;
+use crate::light_sampler::*;
+
+// Everything a path can interact with: the objects, the environment seen by
+// rays that escape them, and the lights sampled directly.
+pub struct Scene<'a> {
+    pub world: HittableList<'a>,
+    pub environment: Arc<dyn Environment>,
+    pub lights: Vec<Arc<dyn Light>>,
+    pub light_sampling: LightSampling,
+    // Light index of each emissive object added with `add_emitter`, by its
+    // index in the world list.
+    emitters: HashMap<usize, usize>,
+    // Built on first use, once all the lights are in.
+    sampler: OnceLock<Box<dyn LightSampler>>
+}
+
+impl<'a> Scene<'a> {
+    pub fn new(world: HittableList<'a>) -> Self {
+        Self {
+            world,
+            environment: Arc::new(GradientSky::default()),
+            lights: Vec::new(),
+            light_sampling: LightSampling::Bvh,
+            emitters: HashMap::new(),
+            sampler: OnceLock::new(),
+        }
+    }
+
+    pub fn with_environment(mut self, environment: Arc<dyn Environment>) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    pub fn with_light_sampling(mut self, light_sampling: LightSampling) -> Self {
+        self.light_sampling = light_sampling;
+        self.sampler = OnceLock::new();
+        self
+    }
+
+    pub fn add_light<T>(&mut self, light: T)
+    where
+        T: 'static + Light
+    {
+        self.lights.push(Arc::new(light));
+        self.sampler = OnceLock::new();
+    }
+
+    // Adds an emissive object to the world and samples it as an area light.
+    pub fn add_emitter<T>(&mut self, object: T)
+    where
+        T: 'static + Hittable
+    {
+        let object: Arc<dyn Hittable> = Arc::new(object);
+        let object_id = self.world.objects.len();
+        self.world.objects.push(object.clone());
+        self.emitters.insert(object_id, self.lights.len());
+        self.lights.push(Arc::new(AreaLight::new(object, object_id)));
+        self.sampler = OnceLock::new();
+    }
+
+    // Light index of the world object `object_id`, if it was added as an
+    // emitter.
+    pub fn emitter(&self, object_id: usize) -> Option<usize> {
+        self.emitters.get(&object_id).copied()
+    }
+
+    pub fn light_sampler(&self) -> &dyn LightSampler {
+        self.sampler.get_or_init(|| self.light_sampling.build(&self.lights)).as_ref()
+    }
+}
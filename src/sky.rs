@@ -0,0 +1,263 @@
+use crate::color::*;
+use crate::constants::PI;
+use crate::environment::Environment;
+use crate::spectrum::*;
+use crate::utilities::{degrees_to_radians, rand_double};
+use crate::vec3::*;
+
+// Mean angular radius of the sun seen from earth, in degrees.
+pub const SUN_ANGULAR_RADIUS: f64 = 0.2666;
+
+// Luminance of the sun's disk above the atmosphere, in the sky model's units
+// of kcd/m^2.
+const SUN_LUMINANCE: f64 = 1.6e6;
+
+// Default scale of the sky, which makes it about as bright as the gradient
+// backdrop.
+pub const SKY_INTENSITY: f64 = 0.05;
+
+// Chance of sampling the sun disk rather than the whole sphere.
+const SUN_SAMPLE_PROBABILITY: f64 = 0.5;
+
+// Perez et al. sky luminance distribution, with the five coefficients of
+// Preetham, Shirley and Smits, "A Practical Analytic Model for Daylight"
+// (1999).
+#[derive(Debug, Clone, Copy)]
+struct Perez {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64
+}
+
+impl Perez {
+    // theta from the zenith to the view direction, gamma between the view
+    // direction and the sun.
+    fn f(&self, cos_theta: f64, gamma: f64) -> f64 {
+        let cos_gamma = gamma.cos();
+        (1.0 + self.a * (self.b / cos_theta.max(0.01)).exp()) * (1.0 + self.c * (self.d * gamma).exp() + self.e * cos_gamma * cos_gamma)
+    }
+}
+
+// Procedural clear sky with a sun disk, lit by the Preetham model. Values are
+// kcd/m^2 times `intensity`. Directions below the horizon see a diffuse
+// ground plane of `ground_albedo` lit by the sun and sky.
+#[derive(Debug, Clone)]
+pub struct PhysicalSky {
+    pub sun_direction: Vec3<f64>,
+    // Haze, from about 2 (very clear) to 10 (hazy).
+    pub turbidity: f64,
+    pub ground_albedo: Color<f64>,
+    // Degrees. Larger suns give softer shadows at the same illuminance.
+    pub sun_angular_radius: f64,
+    pub intensity: f64,
+    perez: [Perez; 3],
+    zenith: [f64; 3],
+    sun_radiance: Color<f64>,
+    sky_irradiance: Color<f64>,
+    ground_radiance: Color<f64>
+}
+
+impl PhysicalSky {
+    // Sun elevation above the horizon and azimuth clockwise from -z towards
+    // +x, both in degrees.
+    pub fn new(elevation: f64, azimuth: f64, turbidity: f64) -> Self {
+        let (e, a) = (degrees_to_radians(elevation), degrees_to_radians(azimuth));
+        let sun_direction = Vec3::new(e.cos() * a.sin(), e.sin(), -e.cos() * a.cos());
+        let zero = Perez { a: 0.0, b: 0.0, c: 0.0, d: 0.0, e: 0.0 };
+        Self {
+            sun_direction,
+            turbidity,
+            ground_albedo: Color::new(0.3, 0.3, 0.3),
+            sun_angular_radius: SUN_ANGULAR_RADIUS,
+            intensity: SKY_INTENSITY,
+            perez: [zero; 3],
+            zenith: [0.0; 3],
+            sun_radiance: Color::zero(),
+            sky_irradiance: Color::zero(),
+            ground_radiance: Color::zero(),
+        }.precompute()
+    }
+
+    pub fn with_ground_albedo(mut self, albedo: Color<f64>) -> Self {
+        self.ground_albedo = albedo;
+        self.precompute()
+    }
+
+    pub fn with_sun_radius(mut self, degrees: f64) -> Self {
+        self.sun_angular_radius = degrees;
+        self.precompute()
+    }
+
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    fn precompute(mut self) -> Self {
+        let t = self.turbidity;
+        let theta_s = self.sun_direction.y.clamp(-1.0, 1.0).acos().min(PI / 2.0);
+
+        // Luminance Y, then chromaticities x and y.
+        self.perez = [
+            Perez { a: 0.1787 * t - 1.4630, b: -0.3554 * t + 0.4275, c: -0.0227 * t + 5.3251, d: 0.1206 * t - 2.5771, e: -0.0670 * t + 0.3703 },
+            Perez { a: -0.0193 * t - 0.2592, b: -0.0665 * t + 0.0008, c: -0.0004 * t + 0.2125, d: -0.0641 * t - 0.8989, e: -0.0033 * t + 0.0452 },
+            Perez { a: -0.0167 * t - 0.2608, b: -0.0950 * t + 0.0092, c: -0.0079 * t + 0.2102, d: -0.0441 * t - 1.6537, e: -0.0109 * t + 0.0529 },
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_y = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let chromaticity = |m: [[f64; 4]; 3]| {
+            let ts = [theta_s.powi(3), theta_s * theta_s, theta_s, 1.0];
+            let row = |r: [f64; 4]| r.iter().zip(ts).map(|(a, b)| a * b).sum::<f64>();
+            t * t * row(m[0]) + t * row(m[1]) + row(m[2])
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_yc = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+        self.zenith = [zenith_y, zenith_x, zenith_yc];
+
+        self.sun_radiance = self.compute_sun_radiance(theta_s);
+
+        // Irradiance on the ground from the sky dome, by midpoint rule.
+        let (n_theta, n_phi) = (16, 32);
+        self.sky_irradiance = Color::zero();
+        for i in 0..n_theta {
+            let theta = (i as f64 + 0.5) / n_theta as f64 * PI / 2.0;
+            for j in 0..n_phi {
+                let phi = (j as f64 + 0.5) / n_phi as f64 * 2.0 * PI;
+                let d = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+                let d_omega = theta.sin() * (PI / 2.0 / n_theta as f64) * (2.0 * PI / n_phi as f64);
+                self.sky_irradiance += (theta.cos() * d_omega) * self.sky(&d);
+            }
+        }
+        self.ground_radiance = self.ground_albedo * (self.sky_irradiance + self.sun_irradiance()) / PI;
+        self
+    }
+
+    // Black body sun reddened by Rayleigh and aerosol extinction along its
+    // path through the atmosphere.
+    fn compute_sun_radiance(&self, theta_s: f64) -> Color<f64> {
+        let zenith_degrees = theta_s * 180.0 / PI;
+        let air_mass = 1.0 / (theta_s.cos() + 0.15 * (93.885 - zenith_degrees).max(1e-3).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+
+        let (mut outside, mut inside) = (Color::zero(), Color::zero());
+        let mut lambda = LAMBDA_MIN;
+        while lambda <= LAMBDA_MAX {
+            let um = lambda * 1e-3;
+            let rayleigh = (-0.008735 * um.powf(-4.08) * air_mass).exp();
+            let aerosol = (-beta * um.powf(-1.3) * air_mass).exp();
+            let emitted = planck(lambda, 5778.0) * cie_xyz(lambda);
+            outside += emitted;
+            inside += (rayleigh * aerosol) * emitted;
+            lambda += 5.0;
+        }
+
+        // Keep the sun's illuminance fixed whatever its apparent size.
+        let size = (SUN_ANGULAR_RADIUS / self.sun_angular_radius).powi(2);
+        let rgb = xyz_to_linear_srgb(inside * (SUN_LUMINANCE * size / outside.y));
+        Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+    }
+
+    fn cos_sun_radius(&self) -> f64 {
+        degrees_to_radians(self.sun_angular_radius).cos()
+    }
+
+    fn sun_solid_angle(&self) -> f64 {
+        2.0 * PI * (1.0 - self.cos_sun_radius())
+    }
+
+    // Irradiance on the ground from the sun disk alone.
+    fn sun_irradiance(&self) -> Color<f64> {
+        (self.sun_solid_angle() * self.sun_direction.y.max(0.0)) * self.sun_radiance
+    }
+
+    // Sky dome alone for a unit direction above the horizon.
+    fn sky(&self, d: &Vec3<f64>) -> Color<f64> {
+        let cos_theta = d.y.max(0.0);
+        let gamma = d.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+        let theta_s = self.sun_direction.y.clamp(-1.0, 1.0).acos().min(PI / 2.0);
+
+        let value = |i: usize| self.zenith[i] * self.perez[i].f(cos_theta, gamma) / self.perez[i].f(1.0, theta_s);
+        let (big_y, x, y) = (value(0), value(1), value(2));
+        if y <= 0.0 {
+            return Color::zero();
+        }
+        let xyz = Color::new(x / y * big_y, big_y, (1.0 - x - y) / y * big_y);
+        let rgb = xyz_to_linear_srgb(xyz);
+        Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+    }
+}
+
+impl Environment for PhysicalSky {
+    fn radiance(&self, direction: &Vec3<f64>) -> Color<f64> {
+        let d = direction.unit_vector();
+        if d.y < 0.0 {
+            return self.intensity * self.ground_radiance;
+        }
+        let mut radiance = self.sky(&d);
+        if d.dot(self.sun_direction) >= self.cos_sun_radius() {
+            radiance += self.sun_radiance;
+        }
+        self.intensity * radiance
+    }
+
+    // Either a direction in the sun's cone, or one uniform over the sphere
+    // for the sky.
+    fn sample(&self) -> Option<(Vec3<f64>, f64)> {
+        let direction = if rand_double() < SUN_SAMPLE_PROBABILITY {
+            let cos_theta = 1.0 - rand_double() * (1.0 - self.cos_sun_radius());
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let phi = 2.0 * PI * rand_double();
+            let (tangent, bitangent) = orthonormal_basis(self.sun_direction);
+            sin_theta * phi.cos() * tangent + sin_theta * phi.sin() * bitangent + cos_theta * self.sun_direction
+        } else {
+            random_unit_vector()
+        };
+        Some((direction, self.pdf(&direction)))
+    }
+
+    fn pdf(&self, direction: &Vec3<f64>) -> f64 {
+        let in_sun = direction.unit_vector().dot(self.sun_direction) >= self.cos_sun_radius();
+        let sun_pdf = if in_sun { 1.0 / self.sun_solid_angle() } else { 0.0 };
+        SUN_SAMPLE_PROBABILITY * sun_pdf + (1.0 - SUN_SAMPLE_PROBABILITY) / (4.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::luminance;
+
+    #[test]
+    fn sky_is_blue_and_sun_dominates() {
+        let sky = PhysicalSky::new(45.0, 0.0, 3.0);
+        let zenith = sky.radiance(&Vec3::new(0.0, 1.0, 0.0));
+        assert!(zenith.z > zenith.x);
+
+        // On a clear day more light reaches the ground from the sun than
+        // from the rest of the sky, and haze shifts it towards the sky.
+        let (sun, dome) = (luminance(&sky.sun_irradiance()), luminance(&sky.sky_irradiance));
+        assert!(sun > dome && dome > 0.1 * sun, "sun {} sky {}", sun, dome);
+        let hazy = PhysicalSky::new(45.0, 0.0, 10.0);
+        assert!(luminance(&hazy.sky_irradiance) / luminance(&hazy.sun_irradiance()) > dome / sun);
+
+        // A low sun is redder than a high one.
+        let sunset = PhysicalSky::new(3.0, 0.0, 3.0);
+        let noon = PhysicalSky::new(80.0, 0.0, 3.0);
+        let ratio = |s: &PhysicalSky| s.sun_radiance.x / s.sun_radiance.z;
+        assert!(ratio(&sunset) > ratio(&noon));
+
+        let (direction, pdf) = sky.sample().unwrap();
+        assert!((pdf - sky.pdf(&direction)).abs() < 1e-9 * pdf);
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::OnceLock;
+
+use crate::color::*;
+use crate::utilities::random_double;
+
+// Visible range covered by the color matching functions, in nanometers.
+pub const LAMBDA_MIN: f64 = 360.0;
+pub const LAMBDA_MAX: f64 = 830.0;
+
+// Piecewise Gaussian used by the CIE fit below.
+fn g(lambda: f64, mu: f64, sigma1: f64, sigma2: f64) -> f64 {
+    let t = (lambda - mu) / if lambda < mu { sigma1 } else { sigma2 };
+    (-0.5 * t * t).exp()
+}
+
+// CIE 1931 2-degree color matching functions, using the multi-lobe fit from
+// Wyman, Sloan and Shirley, "Simple Analytic Approximations to the CIE XYZ
+// Color Matching Functions" (2013). Returned as (x, y, z).
+pub fn cie_xyz(lambda: f64) -> Color<f64> {
+    let x = 1.056 * g(lambda, 599.8, 37.9, 31.0) + 0.362 * g(lambda, 442.0, 16.0, 26.7) - 0.065 * g(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821 * g(lambda, 568.8, 46.9, 40.5) + 0.286 * g(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217 * g(lambda, 437.0, 11.8, 36.0) + 0.681 * g(lambda, 459.0, 26.0, 13.8);
+    Color::new(x, y, z)
+}
+
+pub fn xyz_to_linear_srgb(xyz: Color<f64>) -> Color<f64> {
+    Color::new(
+        3.2404542 * xyz.x - 1.5371385 * xyz.y - 0.4985314 * xyz.z,
+        -0.9692660 * xyz.x + 1.8760108 * xyz.y + 0.0415560 * xyz.z,
+        0.0556434 * xyz.x - 0.2040259 * xyz.y + 1.0572252 * xyz.z,
+    )
+}
+
+// Spectral radiance of a black body (Planck's law), wavelength in nanometers
+// and temperature in Kelvin.
+pub fn planck(lambda: f64, temperature: f64) -> f64 {
+    const C: f64 = 299_792_458.0;
+    const H: f64 = 6.626_070_15e-34;
+    const KB: f64 = 1.380_649e-23;
+
+    let l = lambda * 1e-9;
+    2.0 * H * C * C / (l.powi(5) * ((H * C / (l * KB * temperature)).exp() - 1.0))
+}
+
+// Linear sRGB color of a black body at `temperature`, normalized to unit
+// luminance. Colors outside the sRGB gamut are clipped.
+pub fn blackbody_color(temperature: f64) -> Color<f64> {
+    if temperature <= 0.0 {
+        return Color::zero();
+    }
+
+    let mut xyz = Color::zero();
+    let step = 5.0;
+    let mut lambda = LAMBDA_MIN;
+    while lambda <= LAMBDA_MAX {
+        xyz += planck(lambda, temperature) * cie_xyz(lambda);
+        lambda += step;
+    }
+
+    let rgb = xyz_to_linear_srgb(xyz / xyz.y);
+    Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+}
+
+// Uniformly chosen wavelength for a spectral path; its pdf is 1 / (LAMBDA_MAX - LAMBDA_MIN).
+pub fn sample_wavelength() -> f64 {
+    random_double(LAMBDA_MIN, LAMBDA_MAX)
+}
+
+// Basis spectra from Smits, "An RGB-to-Spectrum Conversion for Reflectances"
+// (1999), tabulated in ten bins over [SMITS_MIN, SMITS_MAX].
+const SMITS_MIN: f64 = 380.0;
+const SMITS_MAX: f64 = 720.0;
+const SMITS_WHITE: [f64; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const SMITS_CYAN: [f64; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const SMITS_MAGENTA: [f64; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const SMITS_YELLOW: [f64; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const SMITS_RED: [f64; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const SMITS_GREEN: [f64; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const SMITS_BLUE: [f64; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+// Value at `lambda` of a smooth spectrum whose color is `rgb`, using Smits'
+// method: the shared part of the channels is white, the rest is built from
+// one secondary and one primary color. Linear in `rgb`, so it also works for
+// emission above one.
+pub fn rgb_to_spectrum(rgb: Color<f64>, lambda: f64) -> f64 {
+    let bin = (((lambda - SMITS_MIN) / (SMITS_MAX - SMITS_MIN) * 10.0).floor().max(0.0) as usize).min(9);
+    let (r, g, b) = (rgb.x, rgb.y, rgb.z);
+
+    if r <= g && r <= b {
+        let rest = if g <= b {
+            (g - r) * SMITS_CYAN[bin] + (b - g) * SMITS_BLUE[bin]
+        } else {
+            (b - r) * SMITS_CYAN[bin] + (g - b) * SMITS_GREEN[bin]
+        };
+        r * SMITS_WHITE[bin] + rest
+    } else if g <= r && g <= b {
+        let rest = if r <= b {
+            (r - g) * SMITS_MAGENTA[bin] + (b - r) * SMITS_BLUE[bin]
+        } else {
+            (b - g) * SMITS_MAGENTA[bin] + (r - b) * SMITS_RED[bin]
+        };
+        g * SMITS_WHITE[bin] + rest
+    } else {
+        let rest = if r <= g {
+            (r - b) * SMITS_YELLOW[bin] + (g - r) * SMITS_GREEN[bin]
+        } else {
+            (g - b) * SMITS_YELLOW[bin] + (r - g) * SMITS_RED[bin]
+        };
+        b * SMITS_WHITE[bin] + rest
+    }
+}
+
+// Linear sRGB of a flat unit spectrum, used to white balance spectral
+// results so that an RGB white round-trips to white.
+fn spectral_white() -> Color<f64> {
+    static WHITE: OnceLock<Color<f64>> = OnceLock::new();
+    *WHITE.get_or_init(|| {
+        let mut xyz = Color::zero();
+        let mut lambda = LAMBDA_MIN;
+        while lambda <= LAMBDA_MAX {
+            xyz += cie_xyz(lambda);
+            lambda += 1.0;
+        }
+        xyz_to_linear_srgb(xyz)
+    })
+}
+
+// Monte Carlo estimate in linear sRGB of a spectrum measured as `radiance` at
+// a single wavelength drawn by `sample_wavelength`.
+pub fn spectral_to_rgb(radiance: f64, lambda: f64) -> Color<f64> {
+    let white = spectral_white();
+    let rgb = xyz_to_linear_srgb(cie_xyz(lambda)) * ((LAMBDA_MAX - LAMBDA_MIN) * radiance);
+    Color::new(rgb.x / white.x, rgb.y / white.y, rgb.z / white.z)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn blackbody_warms_as_it_cools() {
+        let candle = blackbody_color(1900.0);
+        let daylight = blackbody_color(6500.0);
+        let sky = blackbody_color(15000.0);
+
+        assert!(candle.x > candle.y && candle.y > candle.z);
+        assert!(sky.z > sky.x);
+        // 6500 K is close to the sRGB white point.
+        assert!((daylight.x - daylight.z).abs() < 0.1 && (daylight.y - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn rgb_round_trips_through_spectra() {
+        // Averaging over evenly spaced wavelengths stands in for sampling.
+        let steps = 470;
+        for rgb in [Color::new(1.0, 1.0, 1.0), Color::new(0.8, 0.3, 0.1), Color::new(0.1, 0.4, 0.7)] {
+            let mut sum = Color::zero();
+            for i in 0..steps {
+                let lambda = LAMBDA_MIN + (i as f64 + 0.5) * (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+                sum += spectral_to_rgb(rgb_to_spectrum(rgb, lambda), lambda);
+            }
+            let estimate = sum / steps as f64;
+            assert!((estimate - rgb).length() < 0.1, "{} came back as {}", rgb, estimate);
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use std::sync::Arc;
 
+use crate::aabb::*;
 use crate::hittable::*;
+use crate::ray::Ray;
 use crate::vec3::*;
 use crate::material::*;
+use crate::constants::{INFINITY, PI};
+use crate::utilities::rand_double;
 
 #[derive(Clone)]
 pub struct Sphere {
@@ -11,33 +15,94 @@ pub struct Sphere {
     pub mat_ptr: Arc<dyn Material>
 }
 
-impl<'a> Sphere {
+impl Sphere {
     pub fn new(center: Point3<f64>, radius: f64, mat_ptr: Arc<dyn Material>) -> Self { Self { center, radius, mat_ptr } }
+
+    // p: a given point on the sphere of radius one, centered at the origin.
+    // u: returned value [0,1] of angle around the Y axis from X=-1.
+    // v: returned value [0,1] of angle from Y=-1 to Y=+1.
+    pub fn get_sphere_uv(p: &Point3<f64>) -> (f64, f64) {
+        let theta = (-p.y).acos();
+        let phi = (-p.z).atan2(p.x) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+}
+
+// Shared by Sphere and MovingSphere, which differ only in where the center is.
+pub fn hit_sphere(center: Point3<f64>, radius: f64, mat_ptr: &Arc<dyn Material>, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+    let oc = r.origin - center;
+    let a = r.direction.length_squared();
+    let half_b = oc.dot(r.direction);
+    let c = oc.length_squared() - radius * radius;
+
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0. {return false};
+    let sqrtd = discriminant.sqrt();
+
+    // Find the nearest root that lies in the acceptable range.
+    let root = (- half_b - sqrtd ) / a;
+    let root = if root < t_min || t_max < root { (-half_b + sqrtd) / a } else {root};
+    if root < t_min || t_max < root { return false; };
+
+    rec.t = root;
+    rec.p = r.at(rec.t);
+    let outward_normal = (rec.p - center) / radius;
+    rec.set_face_normal(&r, &outward_normal);
+    (rec.u, rec.v) = Sphere::get_sphere_uv(&outward_normal);
+    // Derivatives of the mapping in get_sphere_uv; undefined at the poles.
+    let n = outward_normal;
+    let sin_theta = (n.x * n.x + n.z * n.z).sqrt();
+    if sin_theta > 1e-9 {
+        rec.dpdu = 2.0 * PI * radius * Vec3::new(n.z, 0.0, -n.x);
+        rec.dpdv = PI * radius * Vec3::new(-n.x * n.y / sin_theta, sin_theta, -n.z * n.y / sin_theta);
+    }
+    rec.mat_ptr = mat_ptr.clone();
+
+    true
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, r: crate::ray::Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
-        let oc = r.origin - self.center;
-        let a = r.direction.length_squared();
-        let half_b = oc.dot(r.direction);
-        let c = oc.length_squared() - self.radius * self.radius;
-
-        let discriminant = half_b * half_b - a * c;
-        if discriminant < 0. {return false};
-        let sqrtd = discriminant.sqrt();
-
-        // Find the nearest root that lies in the acceptable range.
-        let root = (- half_b - sqrtd ) / a;
-        let root = if root < t_min || t_max < root { (-half_b + sqrtd) / a } else {root};
-        if root < t_min || t_max < root { return false; };
-
-        rec.t = root;
-        rec.p = r.at(rec.t);
-        let outward_normal = (rec.p - self.center) / self.radius;
-        rec.set_face_normal(&r, &outward_normal);
-        rec.mat_ptr = self.mat_ptr.clone();
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        hit_sphere(self.center, self.radius, &self.mat_ptr, r, t_min, t_max, rec)
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius * self.radius
+    }
+
+    // From outside, a direction in the cone the sphere subtends; from inside,
+    // towards a point chosen uniformly on its surface.
+    fn random(&self, origin: &Point3<f64>) -> Vec3<f64> {
+        let to_center = self.center - *origin;
+        let distance_squared = to_center.length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return (self.center + self.radius * random_unit_vector() - *origin).unit_vector();
+        }
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let cos_theta = 1.0 + rand_double() * (cos_theta_max - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand_double();
+        let w = to_center.unit_vector();
+        let (tangent, bitangent) = orthonormal_basis(w);
+        sin_theta * phi.cos() * tangent + sin_theta * phi.sin() * bitangent + cos_theta * w
+    }
+
+    fn pdf_value(&self, origin: &Point3<f64>, direction: &Vec3<f64>) -> f64 {
+        let distance_squared = (self.center - *origin).length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return area_pdf(self, origin, direction);
+        }
+        let mut rec = HitRecord::zero();
+        if !self.hit(Ray::new(*origin, *direction), 0.001, INFINITY, &mut rec) {
+            return 0.0;
+        }
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+    }
 
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut Aabb) -> bool {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        *output_box = Aabb::new(self.center - r, self.center + r);
         true
     }
 }
-
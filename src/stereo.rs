@@ -0,0 +1,82 @@
+use std::str::FromStr;
+
+use crate::camera::Camera;
+use crate::ray::Ray;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StereoLayout {
+    // Left eye in the left half of the frame.
+    SideBySide,
+    // Left eye in the top half of the frame.
+    TopBottom,
+}
+
+impl FromStr for StereoLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "side-by-side" => Ok(StereoLayout::SideBySide),
+            "top-bottom" => Ok(StereoLayout::TopBottom),
+            _ => Err(format!("unknown stereo layout: {}", s)),
+        }
+    }
+}
+
+// Pair of eyes `ipd` apart around a camera, rendered into one frame. The
+// camera should be built with the aspect ratio of a single eye: half that of
+// the frame side by side, twice that of the frame top to bottom.
+#[derive(Debug, Clone, Copy)]
+pub struct StereoRig {
+    pub left: Camera,
+    pub right: Camera,
+    pub layout: StereoLayout
+}
+
+impl StereoRig {
+    // The eyes converge at `convergence`, which may be infinite for parallel
+    // eyes. Panoramic cameras give omni-directional stereo.
+    pub fn new(camera: &Camera, ipd: f64, convergence: f64, layout: StereoLayout) -> Self {
+        Self { left: camera.eye(-ipd / 2.0, convergence), right: camera.eye(ipd / 2.0, convergence), layout }
+    }
+
+    // Ray for frame coordinates (s, t), from whichever eye covers them.
+    pub fn get_ray(&self, s: f64, t: f64) -> Option<Ray<f64>> {
+        match self.layout {
+            StereoLayout::SideBySide if s < 0.5 => self.left.get_ray(2.0 * s, t),
+            StereoLayout::SideBySide => self.right.get_ray(2.0 * s - 1.0, t),
+            StereoLayout::TopBottom if t >= 0.5 => self.left.get_ray(s, 2.0 * t - 1.0),
+            StereoLayout::TopBottom => self.right.get_ray(s, 2.0 * t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec3::*;
+
+    #[test]
+    fn eyes_converge() {
+        let (lookfrom, lookat, vup) = (Point3::zero(), Point3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0));
+
+        // Center rays of both eyes meet at the convergence distance.
+        let camera = Camera::new(lookfrom, lookat, vup, 60.0, 1.0, 0.0, 1.0);
+        let rig = StereoRig::new(&camera, 0.064, 5.0, StereoLayout::SideBySide);
+        let left = rig.get_ray(0.25, 0.5).unwrap();
+        let right = rig.get_ray(0.75, 0.5).unwrap();
+        assert!((left.origin - Point3::new(-0.032, 0.0, 0.0)).length() < 1e-12);
+        let meet = |r: Ray<f64>| r.at(-5.0 / r.direction.z);
+        assert!((meet(left) - Point3::new(0.0, 0.0, -5.0)).length() < 1e-9);
+        assert!((meet(right) - Point3::new(0.0, 0.0, -5.0)).length() < 1e-9);
+
+        // Omni-directional stereo: looking right, along +x, the left eye has
+        // moved forwards to -z.
+        let panorama = StereoRig::new(&Camera::equirectangular(lookfrom, lookat, vup), 0.064, 5.0, StereoLayout::TopBottom);
+        let left = panorama.get_ray(0.75, 0.75).unwrap();
+        let right = panorama.get_ray(0.75, 0.25).unwrap();
+        assert!((left.origin - Point3::new(0.0, 0.0, -0.032)).length() < 1e-12);
+        assert!((right.origin - Point3::new(0.0, 0.0, 0.032)).length() < 1e-12);
+        assert!((left.at(5.0 / left.direction.x) - Point3::new(5.0, 0.0, 0.0)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,174 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::color::*;
+use crate::perlin::Perlin;
+use crate::vec3::*;
+
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: &Point3<f64>) -> Color<f64>;
+}
+
+pub struct SolidColor {
+    pub color_value: Color<f64>
+}
+
+impl SolidColor {
+    pub fn new(c: &Color<f64>) -> Self {
+        Self { color_value: *c }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3<f64>) -> Color<f64> {
+        self.color_value
+    }
+}
+
+// Alternating 3D cells of two textures, `scale` units across.
+pub struct CheckerTexture {
+    pub odd: Arc<dyn Texture>,
+    pub even: Arc<dyn Texture>,
+    pub scale: f64
+}
+
+impl CheckerTexture {
+    pub fn new(even: Arc<dyn Texture>, odd: Arc<dyn Texture>, scale: f64) -> Self {
+        Self { odd, even, scale }
+    }
+
+    pub fn from_colors(even: &Color<f64>, odd: &Color<f64>, scale: f64) -> Self {
+        Self::new(Arc::new(SolidColor::new(even)), Arc::new(SolidColor::new(odd)), scale)
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: &Point3<f64>) -> Color<f64> {
+        let cell = (p.x / self.scale).floor() + (p.y / self.scale).floor() + (p.z / self.scale).floor();
+        if (cell as i64).rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+// Grayscale Perlin turbulence in [0, 1), handy as a procedural mask.
+pub struct NoiseTexture {
+    pub noise: Perlin,
+    pub scale: f64,
+    pub octaves: usize
+}
+
+impl NoiseTexture {
+    pub fn new(scale: f64, octaves: usize) -> Self {
+        Self { noise: Perlin::new(), scale, octaves }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3<f64>) -> Color<f64> {
+        let t = (0.5 * self.noise.turb(&(self.scale * *p), self.octaves)).min(1.0);
+        Color::new(t, t, t)
+    }
+}
+
+// Image looked up by surface uv, with v = 0 at the bottom row. Nearest
+// neighbour, clamped at the edges.
+pub struct ImageTexture {
+    pub width: usize,
+    pub height: usize,
+    // Linear colors, top row first.
+    pub pixels: Vec<Color<f64>>
+}
+
+impl ImageTexture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color<f64>>) -> Self {
+        assert_eq!(pixels.len(), width * height, "pixel count does not match the image size");
+        Self { width, height, pixels }
+    }
+
+    // Binary (P6) or plain (P3) PPM, decoded with the same gamma 2 that
+    // `write_color` encodes with.
+    pub fn load_ppm<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let bytes = fs::read(path)?;
+
+        // Header: magic, width, height and maxval, separated by whitespace
+        // and '#' comments.
+        let mut pos = 0;
+        let mut fields = Vec::new();
+        while fields.len() < 4 {
+            while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b'#') {
+                if bytes[pos] == b'#' {
+                    while pos < bytes.len() && bytes[pos] != b'\n' {
+                        pos += 1;
+                    }
+                } else {
+                    pos += 1;
+                }
+            }
+            let start = pos;
+            while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if start == pos {
+                return Err(invalid("truncated PPM header"));
+            }
+            fields.push(String::from_utf8_lossy(&bytes[start..pos]).into_owned());
+        }
+        let number = |s: &str| s.parse::<usize>().map_err(|_| invalid("bad number in PPM header"));
+        let (width, height, maxval) = (number(&fields[1])?, number(&fields[2])?, number(&fields[3])?);
+        if maxval == 0 || maxval > 255 {
+            return Err(invalid("only 8-bit PPM files are supported"));
+        }
+
+        let samples: Vec<usize> = match fields[0].as_str() {
+            "P6" => bytes.get(pos + 1..).unwrap_or(&[]).iter().map(|b| *b as usize).collect(),
+            "P3" => {
+                String::from_utf8_lossy(&bytes[pos..]).split_ascii_whitespace().map(number).collect::<io::Result<_>>()?
+            }
+            _ => return Err(invalid("not a PPM file")),
+        };
+        if samples.len() < width * height * 3 {
+            return Err(invalid("PPM file has too few pixels"));
+        }
+
+        let decode = |s: usize| (s as f64 / maxval as f64).powi(2);
+        let pixels = samples.chunks_exact(3).take(width * height)
+            .map(|c| Color::new(decode(c[0]), decode(c[1]), decode(c[2])))
+            .collect();
+        Ok(Self::new(width, height, pixels))
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3<f64>) -> Color<f64> {
+        if self.pixels.is_empty() {
+            return Color::new(0.0, 1.0, 1.0);
+        }
+        let i = ((u.clamp(0.0, 1.0) * self.width as f64) as usize).min(self.width - 1);
+        let j = (((1.0 - v.clamp(0.0, 1.0)) * self.height as f64) as usize).min(self.height - 1);
+        self.pixels[j * self.width + i]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_plain_ppm_bottom_row_at_v_zero() {
+        let path = std::env::temp_dir().join("raychasing_texture_test.ppm");
+        fs::write(&path, "P3\n# two by two\n2 2\n255\n255 0 0  0 255 0\n0 0 255  255 255 255\n").unwrap();
+        let image = ImageTexture::load_ppm(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let p = Point3::zero();
+        assert!((image.value(0.25, 0.75, &p) - Color::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((image.value(0.25, 0.25, &p) - Color::new(0.0, 0.0, 1.0)).length() < 1e-9);
+        assert!((image.value(1.0, 0.0, &p) - Color::new(1.0, 1.0, 1.0)).length() < 1e-9);
+    }
+}
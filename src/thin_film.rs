@@ -0,0 +1,100 @@
+use crate::color::*;
+use crate::constants::PI;
+use crate::spectrum::rgb_to_spectrum;
+
+// Wavelengths in nanometers standing in for the red, green and blue channels
+// when interference is evaluated on an RGB path.
+pub const RGB_WAVELENGTHS: [f64; 3] = [650.0, 532.0, 450.0];
+
+// Thin transparent layer on a surface, e.g. soap, oil or an anodized oxide.
+// Light reflected off its top and bottom interfaces interferes, so the
+// reflectance depends on wavelength, thickness and viewing angle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThinFilm {
+    // Nanometers.
+    pub thickness: f64,
+    pub ior: f64
+}
+
+impl ThinFilm {
+    pub fn new(thickness: f64, ior: f64) -> Self {
+        Self { thickness, ior }
+    }
+
+    // Reflectance of the film over a dielectric with index `eta_t`, lit from a
+    // medium with index `eta_i` at `cos_i` to the normal.
+    pub fn reflectance_dielectric(&self, cos_i: f64, eta_i: f64, eta_t: f64, lambda: f64) -> f64 {
+        let Some(cos_f) = refracted_cos(cos_i, eta_i, self.ior) else { return 1.0; };
+        let (rs_base, rp_base) = match refracted_cos(cos_i, eta_i, eta_t) {
+            Some(cos_t) => fresnel_amplitudes(cos_f, self.ior, cos_t, eta_t),
+            // Total internal reflection at the base.
+            None => (1.0, 1.0),
+        };
+        self.airy(cos_i, eta_i, cos_f, rs_base, rp_base, lambda)
+    }
+
+    // Reflectance of the film over a metal whose bare reflectance is
+    // `base_reflectance`. The metal is treated as an ideal conductor, with a
+    // half wave phase shift on reflection.
+    pub fn reflectance_conductor(&self, cos_i: f64, eta_i: f64, base_reflectance: f64, lambda: f64) -> f64 {
+        let Some(cos_f) = refracted_cos(cos_i, eta_i, self.ior) else { return 1.0; };
+        let r_base = -base_reflectance.clamp(0.0, 1.0).sqrt();
+        self.airy(cos_i, eta_i, cos_f, r_base, r_base, lambda)
+    }
+
+    // Sums the multiply reflected waves inside the film (the Airy formula) for
+    // both polarizations and averages them, for unpolarized light.
+    fn airy(&self, cos_i: f64, eta_i: f64, cos_f: f64, rs_base: f64, rp_base: f64, lambda: f64) -> f64 {
+        let (rs_top, rp_top) = fresnel_amplitudes(cos_i, eta_i, cos_f, self.ior);
+        let cos_delta = (4.0 * PI * self.ior * self.thickness * cos_f / lambda).cos();
+        let reflectance = |r1: f64, r2: f64| {
+            let cross = 2.0 * r1 * r2 * cos_delta;
+            ((r1 * r1 + r2 * r2 + cross) / (1.0 + r1 * r1 * r2 * r2 + cross)).clamp(0.0, 1.0)
+        };
+        0.5 * (reflectance(rs_top, rs_base) + reflectance(rp_top, rp_base))
+    }
+}
+
+// Evaluates `f(lambda, base)` for a path, where `base` is the matching value
+// of an RGB quantity such as the substrate color: at the path's own
+// wavelength on a spectral path, otherwise once per RGB channel.
+pub fn per_channel<F: Fn(f64, f64) -> f64>(wavelength: f64, base: Color<f64>, f: F) -> Color<f64> {
+    if wavelength > 0.0 {
+        let r = f(wavelength, rgb_to_spectrum(base, wavelength));
+        Color::new(r, r, r)
+    } else {
+        Color::new(f(RGB_WAVELENGTHS[0], base.x), f(RGB_WAVELENGTHS[1], base.y), f(RGB_WAVELENGTHS[2], base.z))
+    }
+}
+
+// Cosine of the refracted angle by Snell's law, or None on total internal
+// reflection.
+fn refracted_cos(cos_i: f64, eta_i: f64, eta_t: f64) -> Option<f64> {
+    let sin2_t = (eta_i / eta_t).powi(2) * (1.0 - cos_i * cos_i).max(0.0);
+    if sin2_t >= 1.0 { None } else { Some((1.0 - sin2_t).sqrt()) }
+}
+
+// Signed Fresnel amplitude coefficients (s, p) for an interface.
+fn fresnel_amplitudes(cos_i: f64, eta_i: f64, cos_t: f64, eta_t: f64) -> (f64, f64) {
+    let rs = (eta_i * cos_i - eta_t * cos_t) / (eta_i * cos_i + eta_t * cos_t);
+    let rp = (eta_t * cos_i - eta_i * cos_t) / (eta_t * cos_i + eta_i * cos_t);
+    (rs, rp)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vanishing_film_matches_bare_interface() {
+        // With no thickness the layer drops out: glass at normal incidence.
+        let film = ThinFilm::new(0.0, 1.33);
+        let r = film.reflectance_dielectric(1.0, 1.0, 1.5, 550.0);
+        assert!((r - 0.04).abs() < 1e-9);
+
+        // A quarter wave coating with index sqrt(1.5) cancels reflection.
+        let coating = ThinFilm::new(550.0 / (4.0 * 1.5f64.sqrt()), 1.5f64.sqrt());
+        assert!(coating.reflectance_dielectric(1.0, 1.0, 1.5, 550.0) < 1e-9);
+        assert!(coating.reflectance_dielectric(1.0, 1.0, 1.5, 400.0) > 1e-3);
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::constants::PI;
+use crate::hittable::*;
+use crate::poly::solve_quartic;
+use crate::ray::Ray;
+use crate::vec3::*;
+use crate::material::*;
+
+// Torus around `axis` through `center`: a tube of `minor_radius` swept along
+// a circle of `major_radius`.
+#[derive(Clone)]
+pub struct Torus {
+    pub center: Point3<f64>,
+    pub axis: Vec3<f64>,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub mat_ptr: Arc<dyn Material>
+}
+
+impl Torus {
+    pub fn new(center: Point3<f64>, axis: Vec3<f64>, major_radius: f64, minor_radius: f64, mat_ptr: Arc<dyn Material>) -> Self {
+        Self { center, axis: axis.unit_vector(), major_radius, minor_radius, mat_ptr }
+    }
+
+    // Local frame with the torus axis as y.
+    fn frame(&self) -> (Vec3<f64>, Vec3<f64>, Vec3<f64>) {
+        let (x, z) = orthonormal_basis(self.axis);
+        (x, self.axis, z)
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let (ex, ey, ez) = self.frame();
+        let oc = r.origin - self.center;
+        let o = Vec3::new(oc.dot(ex), oc.dot(ey), oc.dot(ez));
+        // Solve with a unit direction for a well conditioned quartic.
+        let scale = r.direction.length();
+        let d = Vec3::new(r.direction.dot(ex), r.direction.dot(ey), r.direction.dot(ez)) / scale;
+
+        let (a, b) = (self.major_radius, self.minor_radius);
+        let four_a_sqrd = 4.0 * a * a;
+        let e = o.length_squared() - a * a - b * b;
+        let f = o.dot(d);
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            2.0 * e + 4.0 * f * f + four_a_sqrd * d.y * d.y,
+            4.0 * f * e + 2.0 * four_a_sqrd * o.y * d.y,
+            e * e - four_a_sqrd * (b * b - o.y * o.y),
+        );
+
+        let Some(t) = roots.into_iter().map(|t| t / scale).filter(|t| *t >= t_min && *t <= t_max).reduce(f64::min) else {
+            return false;
+        };
+
+        // Normal points from the nearest point on the sweep circle.
+        let p = o + (t * scale) * d;
+        let ring = Vec3::new(p.x, 0.0, p.z);
+        let ring = if ring.near_zero() { Vec3::new(a, 0.0, 0.0) } else { a * ring.unit_vector() };
+        let local_normal = (p - ring).unit_vector();
+
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.u = (p.z.atan2(p.x) + PI) / (2.0 * PI);
+        rec.v = (local_normal.y.atan2(ring.unit_vector().dot(local_normal)) + PI) / (2.0 * PI);
+        let to_world = |v: Vec3<f64>| v.x * ex + v.y * ey + v.z * ez;
+        rec.set_face_normal(&r, &to_world(local_normal));
+        // u turns the point around the axis, v around the tube.
+        let ring_direction = ring.unit_vector();
+        let around_tube = local_normal.dot(ring_direction) * Vec3::new(0.0, 1.0, 0.0) - local_normal.y * ring_direction;
+        rec.dpdu = 2.0 * PI * to_world(Vec3::new(-p.z, 0.0, p.x));
+        rec.dpdv = 2.0 * PI * self.minor_radius * to_world(around_tube);
+        rec.mat_ptr = self.mat_ptr.clone();
+
+        true
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut Aabb) -> bool {
+        let ring = disk_box(self.center, self.axis, self.major_radius);
+        let b = Vec3::new(self.minor_radius, self.minor_radius, self.minor_radius);
+        *output_box = Aabb::new(ring.minimum - b, ring.maximum + b);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Torus;
+    use crate::color::Color;
+    use crate::hittable::*;
+    use crate::hittable::test::check_surface_derivatives;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::*;
+
+    #[test]
+    fn hits_tube_and_misses_hole() {
+        let torus = Torus::new(Point3::zero(), Vec3::new(0.0, 1.0, 0.0), 2.0, 0.5, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        let mut rec = HitRecord::zero();
+
+        // Down through the tube at x = 2: hits the top of the tube at y = 0.5.
+        let r = Ray::new(Point3::new(2.0, 5.0, 0.0), Vec3::new(0.0, -2.0, 0.0));
+        assert!(torus.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.p.y - 0.5).abs() < 1e-6);
+        assert!((rec.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-6);
+        assert!(rec.front_face);
+
+        // Down through the hole.
+        let r = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(!torus.hit(r, 0.001, f64::INFINITY, &mut rec));
+
+        // Along x from outside: enters the tube at x = -2.5.
+        let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(torus.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn derivatives_follow_uv() {
+        let torus = Torus::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.2, 1.0, 0.1), 2.0, 0.5, Arc::new(Lambertian::new(&Color::new(0.5, 0.5, 0.5))));
+        check_surface_derivatives(&torus, Ray::new(Point3::new(5.0, 1.2, 0.7), Vec3::new(-1.0, 0.0, 0.0)));
+        check_surface_derivatives(&torus, Ray::new(Point3::new(1.6, 6.0, 1.0), Vec3::new(0.0, -1.0, 0.0)));
+    }
+}
@@ -0,0 +1,224 @@
+use std::ops::Mul;
+
+use crate::aabb::*;
+use crate::ray::*;
+use crate::utilities::degrees_to_radians;
+use crate::vec3::*;
+
+pub type Matrix4 = [[f64; 4]; 4];
+
+const IDENTITY: Matrix4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+// Affine transform stored as a 4x4 matrix together with its inverse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub m: Matrix4,
+    pub m_inv: Matrix4
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self { m: IDENTITY, m_inv: IDENTITY }
+    }
+
+    // Returns None for a singular matrix.
+    pub fn from_matrix(m: Matrix4) -> Option<Self> {
+        invert(&m).map(|m_inv| Self { m, m_inv })
+    }
+
+    pub fn translate(delta: Vec3<f64>) -> Self {
+        let mut m = IDENTITY;
+        let mut m_inv = IDENTITY;
+        for (i, d) in delta.to_vec().iter().enumerate() {
+            m[i][3] = *d;
+            m_inv[i][3] = -*d;
+        }
+        Self { m, m_inv }
+    }
+
+    pub fn scale(factors: Vec3<f64>) -> Self {
+        let mut m = IDENTITY;
+        let mut m_inv = IDENTITY;
+        for (i, f) in factors.to_vec().iter().enumerate() {
+            m[i][i] = *f;
+            m_inv[i][i] = 1.0 / *f;
+        }
+        Self { m, m_inv }
+    }
+
+    // Rotation about `axis` by `degrees`, counterclockwise looking down the axis.
+    pub fn rotate(axis: Vec3<f64>, degrees: f64) -> Self {
+        let a = axis.unit_vector();
+        let (sin_theta, cos_theta) = degrees_to_radians(degrees).sin_cos();
+        let mut m = IDENTITY;
+        m[0][0] = a.x * a.x + (1.0 - a.x * a.x) * cos_theta;
+        m[0][1] = a.x * a.y * (1.0 - cos_theta) - a.z * sin_theta;
+        m[0][2] = a.x * a.z * (1.0 - cos_theta) + a.y * sin_theta;
+        m[1][0] = a.x * a.y * (1.0 - cos_theta) + a.z * sin_theta;
+        m[1][1] = a.y * a.y + (1.0 - a.y * a.y) * cos_theta;
+        m[1][2] = a.y * a.z * (1.0 - cos_theta) - a.x * sin_theta;
+        m[2][0] = a.x * a.z * (1.0 - cos_theta) - a.y * sin_theta;
+        m[2][1] = a.y * a.z * (1.0 - cos_theta) + a.x * sin_theta;
+        m[2][2] = a.z * a.z + (1.0 - a.z * a.z) * cos_theta;
+        // Rotations are orthogonal, so the inverse is the transpose.
+        Self { m, m_inv: transpose(&m) }
+    }
+
+    pub fn rotate_x(degrees: f64) -> Self { Self::rotate(Vec3::new(1.0, 0.0, 0.0), degrees) }
+    pub fn rotate_y(degrees: f64) -> Self { Self::rotate(Vec3::new(0.0, 1.0, 0.0), degrees) }
+    pub fn rotate_z(degrees: f64) -> Self { Self::rotate(Vec3::new(0.0, 0.0, 1.0), degrees) }
+
+    pub fn inverse(&self) -> Self {
+        Self { m: self.m_inv, m_inv: self.m }
+    }
+
+    pub fn point(&self, p: Point3<f64>) -> Point3<f64> {
+        let m = &self.m;
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3];
+        let z = m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3];
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        if w == 1.0 { Point3::new(x, y, z) } else { Point3::new(x, y, z) / w }
+    }
+
+    pub fn vector(&self, v: Vec3<f64>) -> Vec3<f64> {
+        let m = &self.m;
+        Vec3::new(m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+                  m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+                  m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z)
+    }
+
+    // Normals transform by the inverse transpose; the result is not normalized.
+    pub fn normal(&self, n: Vec3<f64>) -> Vec3<f64> {
+        let m = &self.m_inv;
+        Vec3::new(m[0][0] * n.x + m[1][0] * n.y + m[2][0] * n.z,
+                  m[0][1] * n.x + m[1][1] * n.y + m[2][1] * n.z,
+                  m[0][2] * n.x + m[1][2] * n.y + m[2][2] * n.z)
+    }
+
+    // The direction is not renormalized, so hit distances t carry over
+    // unchanged between the two spaces.
+    pub fn ray(&self, r: &Ray<f64>) -> Ray<f64> {
+        let mut transformed = *r;
+        transformed.origin = self.point(r.origin);
+        transformed.direction = self.vector(r.direction);
+        transformed
+    }
+
+    pub fn bounding_box(&self, b: &Aabb) -> Aabb {
+        let mut result: Option<Aabb> = None;
+        for i in 0..8 {
+            let corner = Point3::new(
+                if i & 1 == 0 { b.minimum.x } else { b.maximum.x },
+                if i & 2 == 0 { b.minimum.y } else { b.maximum.y },
+                if i & 4 == 0 { b.minimum.z } else { b.maximum.z },
+            );
+            let p = self.point(corner);
+            let corner_box = Aabb::new(p, p);
+            result = Some(match result {
+                Some(r) => surrounding_box(&r, &corner_box),
+                None => corner_box,
+            });
+        }
+        result.unwrap()
+    }
+}
+
+impl Mul for Transform {
+    type Output = Transform;
+
+    // `a * b` applies b first, then a.
+    fn mul(self, rhs: Transform) -> Transform {
+        Transform { m: multiply(&self.m, &rhs.m), m_inv: multiply(&rhs.m_inv, &self.m_inv) }
+    }
+}
+
+fn multiply(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+    let mut r = [[0.0; 4]; 4];
+    for (i, row) in r.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    r
+}
+
+fn transpose(m: &Matrix4) -> Matrix4 {
+    let mut r = [[0.0; 4]; 4];
+    for (i, row) in r.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = m[j][i];
+        }
+    }
+    r
+}
+
+// Gauss-Jordan elimination with partial pivoting.
+fn invert(m: &Matrix4) -> Option<Matrix4> {
+    if m.iter().flatten().any(|v| !v.is_finite()) {
+        return None;
+    }
+    let mut a = *m;
+    let mut inv = IDENTITY;
+    for col in 0..4 {
+        let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+
+        let scale = 1.0 / a[col][col];
+        for j in 0..4 {
+            a[col][j] *= scale;
+            inv[col][j] *= scale;
+        }
+        for row in 0..4 {
+            if row != col {
+                let factor = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= factor * a[col][j];
+                    inv[row][j] -= factor * inv[col][j];
+                }
+            }
+        }
+    }
+    Some(inv)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn composed_transform_inverts() {
+        let t = Transform::translate(Vec3::new(1.0, 2.0, 3.0)) * Transform::rotate_y(30.0) * Transform::scale(Vec3::new(2.0, 0.5, 1.0));
+        let p = Point3::new(0.3, -1.2, 4.0);
+        assert!((t.inverse().point(t.point(p)) - p).length() < 1e-12);
+
+        let mut broken = IDENTITY;
+        broken[1][2] = f64::NAN;
+        assert!(Transform::from_matrix(broken).is_none());
+
+        let general = Transform::from_matrix(t.m).unwrap();
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((general.m_inv[i][j] - t.m_inv[i][j]).abs() < 1e-12);
+            }
+        }
+        assert!(Transform::from_matrix([[0.0; 4]; 4]).is_none());
+    }
+
+    #[test]
+    fn normals_stay_perpendicular() {
+        let t = Transform::scale(Vec3::new(4.0, 1.0, 1.0)) * Transform::rotate_z(45.0);
+        let tangent = Vec3::new(1.0, -1.0, 0.0);
+        let normal = Vec3::new(1.0, 1.0, 0.0);
+        assert!(t.vector(tangent).dot(t.normal(normal)).abs() < 1e-12);
+    }
+}
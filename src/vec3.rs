@@ -14,7 +14,7 @@ pub type Point3<T> = Vec3<T>;
 
 impl Vec3<f64> {
     pub fn new(x: f64, y: f64, z: f64) -> Self {
-        Vec3 { x: x, y: y, z: z }
+        Vec3 { x, y, z }
     }
 
     pub fn zero() -> Self {
@@ -122,7 +122,7 @@ impl Sub<f64> for Vec3<f64> {
     type Output = Self;
     #[inline]
     fn sub(self, rhs: f64) -> Self {
-        Self::new(  self.x + rhs, self.y + rhs, self.z + rhs )
+        Self::new(  self.x - rhs, self.y - rhs, self.z - rhs )
     }
 }
 
@@ -130,7 +130,7 @@ impl Sub<Vec3<f64>> for f64 {
     type Output = Vec3<f64>;
     #[inline]
     fn sub(self, rhs: Vec3<f64>) -> Vec3<f64> {
-        Vec3::new(self + rhs.x, self + rhs.y, self + rhs.z)
+        Vec3::new(self - rhs.x, self - rhs.y, self - rhs.z)
     }
 }
 
@@ -252,6 +252,22 @@ pub fn random_in_unit_disk() -> Vec3<f64> {
     }
 }
 
+// Two unit vectors completing an orthonormal basis with the unit vector `n`
+// (Duff et al., "Building an Orthonormal Basis, Revisited").
+pub fn orthonormal_basis(n: Vec3<f64>) -> (Vec3<f64>, Vec3<f64>) {
+    let sign = 1.0f64.copysign(n.z);
+    let a = -1.0 / (sign + n.z);
+    let b = n.x * n.y * a;
+    (Vec3::new(1.0 + sign * n.x * n.x * a, sign * b, -sign * n.x),
+     Vec3::new(b, sign + n.y * n.y * a, -n.y))
+}
+
+// Velocity of a point `radial` away from an axis as it turns around it, per
+// radian measured from `tangent` towards `bitangent`.
+pub fn azimuth_derivative(radial: Vec3<f64>, tangent: Vec3<f64>, bitangent: Vec3<f64>) -> Vec3<f64> {
+    radial.dot(tangent) * bitangent - radial.dot(bitangent) * tangent
+}
+
 #[cfg(test)]
 mod test {
     use super::Vec3;
@@ -267,4 +283,13 @@ mod test {
         let etai_over_etat = 1.5;
         print!("{:?}", r_in.refract(n, etai_over_etat))
     }
+
+    #[test]
+    fn scalar_subtraction() {
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        let a = v - 1.0;
+        assert_eq!((a.x, a.y, a.z), (0.0, 1.0, 2.0));
+        let b = 1.0 - v;
+        assert_eq!((b.x, b.y, b.z), (0.0, -1.0, -2.0));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,218 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::aabb::*;
+use crate::constants::INFINITY;
+use crate::hittable::*;
+use crate::material::*;
+use crate::perlin::Perlin;
+use crate::ray::Ray;
+use crate::utilities::rand_double;
+use crate::vec3::*;
+
+// Scalar field over space, such as density or temperature.
+pub trait VolumeField: Send + Sync {
+    fn value(&self, p: &Point3<f64>) -> f64;
+
+    // Upper bound on `value` anywhere, used as the tracking majorant.
+    fn max_value(&self) -> f64;
+}
+
+// Dense voxel array stretched over `bounds`, sampled with trilinear
+// interpolation. Values are stored with x varying fastest, then y, then z.
+pub struct VoxelGrid {
+    pub nx: usize,
+    pub ny: usize,
+    pub nz: usize,
+    pub data: Vec<f32>,
+    pub bounds: Aabb,
+    max: f64
+}
+
+impl VoxelGrid {
+    pub fn new(nx: usize, ny: usize, nz: usize, data: Vec<f32>, bounds: Aabb) -> Self {
+        assert!(nx > 0 && ny > 0 && nz > 0, "voxel grid needs at least one voxel along each axis");
+        assert_eq!(data.len(), nx * ny * nz, "voxel data does not match the grid resolution");
+        let max = data.iter().fold(0.0f32, |m, v| m.max(*v)) as f64;
+        Self { nx, ny, nz, data, bounds, max }
+    }
+
+    // Headerless file of little-endian f32 values, as written by most
+    // simulation exporters.
+    pub fn load_raw<P: AsRef<Path>>(path: P, nx: usize, ny: usize, nz: usize, bounds: Aabb) -> io::Result<Self> {
+        if nx == 0 || ny == 0 || nz == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "voxel grid needs at least one voxel along each axis"));
+        }
+        let bytes = fs::read(path)?;
+        if bytes.len() != nx * ny * nz * 4 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("expected {} voxels, file has {} bytes", nx * ny * nz, bytes.len())));
+        }
+        let data = bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        Ok(Self::new(nx, ny, nz, data, bounds))
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.data[(z * self.ny + y) * self.nx + x] as f64
+    }
+}
+
+impl VolumeField for VoxelGrid {
+    fn value(&self, p: &Point3<f64>) -> f64 {
+        let size = self.bounds.maximum - self.bounds.minimum;
+        let local = p.to_vec();
+        let min = self.bounds.minimum.to_vec();
+        let extent = size.to_vec();
+        let res = [self.nx, self.ny, self.nz];
+
+        // Continuous voxel coordinates with samples at voxel centers.
+        let mut base = [0usize; 3];
+        let mut frac = [0.0; 3];
+        for a in 0..3 {
+            let c = (local[a] - min[a]) / extent[a] * res[a] as f64 - 0.5;
+            if c < -0.5 || c > res[a] as f64 - 0.5 {
+                return 0.0;
+            }
+            let c = c.clamp(0.0, (res[a] - 1) as f64);
+            base[a] = (c.floor() as usize).min(res[a].saturating_sub(2));
+            frac[a] = if res[a] > 1 { c - base[a] as f64 } else { 0.0 };
+        }
+
+        let mut value = 0.0;
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let mut weight = 1.0;
+            let mut index = [0usize; 3];
+            for a in 0..3 {
+                weight *= if offset[a] == 1 { frac[a] } else { 1.0 - frac[a] };
+                index[a] = (base[a] + offset[a]).min(res[a] - 1);
+            }
+            value += weight * self.voxel(index[0], index[1], index[2]);
+        }
+        value
+    }
+
+    fn max_value(&self) -> f64 {
+        self.max
+    }
+}
+
+// Procedural billowing density from Perlin turbulence.
+pub struct NoiseField {
+    pub noise: Perlin,
+    pub frequency: f64,
+    pub octaves: usize
+}
+
+impl NoiseField {
+    pub fn new(frequency: f64, octaves: usize) -> Self {
+        Self { noise: Perlin::new(), frequency, octaves }
+    }
+}
+
+impl VolumeField for NoiseField {
+    fn value(&self, p: &Point3<f64>) -> f64 {
+        self.noise.turb(&(self.frequency * *p), self.octaves)
+    }
+
+    fn max_value(&self) -> f64 {
+        // Octave weights 1, 1/2, 1/4, ... sum to less than two.
+        2.0
+    }
+}
+
+// Participating medium with spatially varying density inside a closed
+// boundary, sampled with delta tracking.
+#[derive(Clone)]
+pub struct HeterogeneousMedium {
+    pub boundary: Arc<dyn Hittable>,
+    pub density: Arc<dyn VolumeField>,
+    pub density_scale: f64,
+    pub phase_function: Arc<dyn Material>
+}
+
+impl HeterogeneousMedium {
+    pub fn new(boundary: Arc<dyn Hittable>, density: Arc<dyn VolumeField>, density_scale: f64, phase_function: Arc<dyn Material>) -> Self {
+        Self { boundary, density, density_scale, phase_function }
+    }
+
+    fn majorant(&self) -> f64 {
+        self.density.max_value() * self.density_scale
+    }
+
+    // Entry and exit distances through the boundary, clipped to [t_min, t_max].
+    fn segment(&self, r: &Ray<f64>, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut rec1 = HitRecord::zero();
+        let mut rec2 = HitRecord::zero();
+        if !self.boundary.hit(*r, -INFINITY, INFINITY, &mut rec1) {
+            return None;
+        }
+        if !self.boundary.hit(*r, rec1.t + 0.0001, INFINITY, &mut rec2) {
+            return None;
+        }
+        let t0 = rec1.t.max(t_min).max(0.0);
+        let t1 = rec2.t.min(t_max);
+        if t0 < t1 { Some((t0, t1)) } else { None }
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, r: Ray<f64>, t_min: f64, t_max: f64, rec: &mut HitRecord<f64>) -> bool {
+        let Some((t0, t1)) = self.segment(&r, t_min, t_max) else { return false; };
+        let majorant = self.majorant();
+        if majorant <= 0.0 {
+            return false;
+        }
+
+        // Delta tracking: take steps through a fictitious homogeneous medium
+        // of density `majorant` and accept each collision as real with
+        // probability density / majorant.
+        let ray_length = r.direction.length();
+        let mut t = t0;
+        loop {
+            t += -(1.0 - rand_double()).ln() / (majorant * ray_length);
+            if t >= t1 {
+                return false;
+            }
+            let p = r.at(t);
+            let density = self.density.value(&p) * self.density_scale;
+            if rand_double() < density / majorant {
+                rec.t = t;
+                rec.p = p;
+                rec.normal = Vec3::new(1.0, 0.0, 0.0);
+                rec.geometric_normal = rec.normal;
+                rec.front_face = true;
+                rec.mat_ptr = self.phase_function.clone();
+                return true;
+            }
+        }
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut Aabb) -> bool {
+        self.boundary.bounding_box(time0, time1, output_box)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn voxel_grid_interpolates_between_centers() {
+        // Two voxels along x over [0, 2]: centers at x = 0.5 and x = 1.5.
+        let grid = VoxelGrid::new(2, 1, 1, vec![0.0, 1.0], Aabb::new(Point3::zero(), Point3::new(2.0, 1.0, 1.0)));
+        let at = |x: f64| grid.value(&Point3::new(x, 0.5, 0.5));
+        assert!((at(0.5) - 0.0).abs() < 1e-9);
+        assert!((at(1.0) - 0.5).abs() < 1e-9);
+        assert!((at(1.9) - 1.0).abs() < 1e-9);
+        assert_eq!(at(3.0), 0.0);
+        assert_eq!(grid.max_value(), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one voxel")]
+    fn voxel_grid_rejects_empty_axes() {
+        VoxelGrid::new(2, 0, 1, vec![], Aabb::new(Point3::zero(), Point3::new(1.0, 1.0, 1.0)));
+    }
+}